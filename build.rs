@@ -14,9 +14,9 @@ mod config {
 
 #[cfg(not(target_os = "windows"))]
 mod config {
-    pub(crate) const CADDY_BIN_FILE: &'static str = "caddy";
-    pub(crate) const SHELL_TO_EXEC: &'static str = "bash";
-    pub(crate) const DOWNLOAD_CADDY_SCRIPT: &'static str = "download_caddy.bash";
+    pub(crate) const CADDY_BIN_FILE: &str = "caddy";
+    pub(crate) const SHELL_TO_EXEC: &str = "bash";
+    pub(crate) const DOWNLOAD_CADDY_SCRIPT: &str = "download_caddy.bash";
 }
 
 fn main() {
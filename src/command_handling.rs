@@ -1,9 +1,10 @@
 use clap::ArgMatches;
 use clap::Command as ClapCommand;
+use std::ffi::OsString;
 use std::process::ExitCode;
 
 pub(crate) struct CommandList {
-    pub(crate) commands: Vec<Box<CommandHandler>>,
+    pub(crate) commands: Vec<CommandHandler>,
 }
 
 impl CommandList {
@@ -13,15 +14,68 @@ impl CommandList {
             commands
         })
     }
+
+    /// Passthrough commands forward all their arguments to another program.
+    /// A "--" separator is inserted right after their name,
+    /// so that arguments like "-v" are not parsed as Rymfony's own global options.
+    pub(crate) fn prepare_args(&self, application: &ClapCommand, args: Vec<OsString>) -> Vec<OsString> {
+        let subcommand_position = match find_subcommand_position(application, &args) {
+            Some(position) => position,
+            None => return args,
+        };
+
+        let is_passthrough = self
+            .commands
+            .iter()
+            .any(|command| command.passthrough && args[subcommand_position] == command.command_definition.get_name());
+
+        let mut args = args;
+        if is_passthrough {
+            args.insert(subcommand_position + 1, OsString::from("--"));
+        }
+
+        args
+    }
+}
+
+/// Skips the global options preceding the subcommand, with the values of those taking one, like "--project-dir DIR".
+fn find_subcommand_position(application: &ClapCommand, args: &[OsString]) -> Option<usize> {
+    let takes_value = |arg: &str| {
+        application.get_arguments().any(|option| {
+            option.get_action().takes_values()
+                && (option.get_long().is_some_and(|long| arg == format!("--{}", long))
+                    || option.get_short().is_some_and(|short| arg == format!("-{}", short)))
+        })
+    };
+
+    let mut position = 1;
+    while position < args.len() {
+        let arg = args[position].to_string_lossy();
+        if !arg.starts_with('-') {
+            return Some(position);
+        }
+
+        position += if takes_value(&arg) { 2 } else { 1 };
+    }
+
+    None
 }
 
 pub(crate) struct CommandHandler {
     pub(crate) command_definition: ClapCommand,
     pub(crate) executor: Box<dyn Fn(&ArgMatches) -> ExitCode>,
+    pub(crate) passthrough: bool,
 }
 
 impl CommandHandler {
     pub(crate) fn new(command_definition: ClapCommand, executor: Box<dyn Fn(&ArgMatches) -> ExitCode>) -> Self {
-        Self { command_definition, executor }
+        Self { command_definition, executor, passthrough: false }
+    }
+
+    pub(crate) fn new_passthrough(
+        command_definition: ClapCommand,
+        executor: Box<dyn Fn(&ArgMatches) -> ExitCode>,
+    ) -> Self {
+        Self { command_definition, executor, passthrough: true }
     }
 }
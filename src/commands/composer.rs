@@ -0,0 +1,45 @@
+use crate::command_handling::CommandHandler;
+use crate::php::cli;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new_passthrough(
+        ClapCommand::new("composer")
+            .about("Run Composer with the project's PHP version")
+            .after_help(
+                "
+Composer is searched as a \"composer.phar\" file in the project directory, and then in the PATH.
+
+All arguments are passed to Composer, and the exit code of Composer is returned.
+",
+            )
+            .disable_help_flag(true)
+            .disable_version_flag(true)
+            .arg(
+                Arg::new("arguments")
+                    .help("The arguments passed to Composer")
+                    .num_args(0..)
+                    .trailing_var_arg(true)
+                    .allow_hyphen_values(true),
+            ),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let arguments: Vec<&String> = args.get_many::<String>("arguments").unwrap_or_default().collect();
+
+    let mut command = match cli::get_composer_command() {
+        Ok(command) => command,
+        Err(e) => {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    cli::to_exit_code(command.args(arguments).status())
+}
@@ -0,0 +1,56 @@
+use crate::command_handling::CommandHandler;
+use crate::php::cli;
+use crate::utils::project_directory::get_project_root;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new_passthrough(
+        ClapCommand::new("console")
+            .about("Run the Symfony console (bin/console) with the project's PHP version")
+            .after_help(
+                "
+All arguments are passed to the console, and the exit code of the console is returned.
+
+Example:
+
+$ rymfony console cache:clear
+",
+            )
+            .disable_help_flag(true)
+            .disable_version_flag(true)
+            .arg(
+                Arg::new("arguments")
+                    .help("The arguments passed to the console")
+                    .num_args(0..)
+                    .trailing_var_arg(true)
+                    .allow_hyphen_values(true),
+            ),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let arguments: Vec<&String> = args.get_many::<String>("arguments").unwrap_or_default().collect();
+
+    let console_path = get_project_root().join("bin").join("console");
+
+    if !console_path.is_file() {
+        error!("No Symfony console found at \"{}\".", console_path.to_str().unwrap());
+
+        return ExitCode::from(1);
+    }
+
+    let mut command = match cli::get_php_cli_command() {
+        Ok(command) => command,
+        Err(e) => {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    cli::to_exit_code(command.arg(console_path).args(arguments).status())
+}
@@ -1,11 +1,25 @@
 use crate::command_handling::CommandHandler;
 use crate::config::paths;
+use crate::php::error_log;
+use crate::php::error_log::ErrorLogParser;
+use crate::php::error_log::Severity;
+use crate::utils::project_directory::get_project_root;
 use clap::arg;
 use clap::ArgMatches;
 use clap::Command as ClapCommand;
 use colored::*;
 use linemux::MuxedLines;
+use std::collections::HashMap;
 use std::process::ExitCode;
+use std::time::Duration;
+#[cfg(not(target_family = "windows"))]
+use {
+    crate::php::slow_log, crate::php::slow_log::SlowLogEntry, crate::php::slow_log::SlowLogParser,
+    std::fs::read_to_string,
+};
+
+// Multi-line entries are written at once, so they are complete when no more lines come.
+const LOG_ENTRY_DELAY: Duration = Duration::from_millis(300);
 
 pub(crate) fn get_command() -> CommandHandler {
     CommandHandler::new(
@@ -14,60 +28,146 @@ pub(crate) fn get_command() -> CommandHandler {
             .alias("local:server:log") // For Symfony CLI compat
             .alias("server:log") // For Symfony CLI compat
             .about("Display server logs")
-            .arg(arg!(<channel> "The optional logging channel you want to display"))
+            .arg(arg!([channel] "The optional logging channel you want to display: \"php\" for PHP errors, or \"slow\" for slow PHP requests"))
             .arg(arg!(--"no-follow" "Do not tail the logs").alias("no-tail"))
-            .arg(arg!(-n --lines <LINES> "Number of lines to display at start").default_value("0")),
+            .arg(arg!(-n --lines <LINES> "Number of lines to display at start").default_value("0"))
+            .arg(
+                arg!(--level <LEVEL> "Only display PHP errors of this level or more severe")
+                    .value_parser(error_log::SEVERITY_NAMES),
+            ),
         Box::new(execute),
     )
 }
 
-pub(crate) fn execute(_args: &ArgMatches) -> ExitCode {
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let channel = args.get_one::<String>("channel").map(|s| s.as_str());
+    if channel == Some("slow") {
+        return tail_slow_log(args);
+    }
+
+    let level: Severity =
+        args.get_one::<String>("level").map_or(Severity::Debug, |level| level.parse().unwrap_or(Severity::Debug));
+
+    // PHP error logs are grouped into records, other logs are displayed line by line.
+    let php_error_files = vec![
+        paths::get_php_server_error_file(),
+        paths::get_php_process_err_file(),
+        paths::get_php_cgi_log_file(),
+    ];
+
+    let mut log_files = php_error_files.clone();
+    if channel != Some("php") {
+        log_files.extend(vec![
+            paths::get_rymfony_process_log_file(),
+            paths::get_rymfony_process_err_file(),
+            paths::get_http_process_stdout_file(),
+            paths::get_http_process_stderr_file(),
+            paths::get_http_server_log_file(),
+            paths::get_http_vhost_log_file(),
+            paths::get_php_process_log_file(),
+            paths::get_php_server_log_file(),
+        ]);
+    }
+
+    let project_dir = get_project_root();
+    let mut parsers: HashMap<String, ErrorLogParser> = php_error_files
+        .iter()
+        .map(|file| (file.file_name().unwrap().to_str().unwrap().to_string(), ErrorLogParser::new()))
+        .collect();
+
+    let print_record = |source: &str, record: error_log::ErrorLogRecord| {
+        if record.severity >= level {
+            println!("[{}] - {}", source.green(), error_log::render(&record, &project_dir));
+        }
+    };
+
     let rt = tokio::runtime::Runtime::new().unwrap();
 
     rt.block_on(async {
         let mut lines = MuxedLines::new().unwrap();
 
-        let rymfony_process_log_file = paths::get_rymfony_process_log_file();
-        let rymfony_process_err_file = paths::get_rymfony_process_err_file();
-        let http_process_stdout_file = paths::get_http_process_stdout_file();
-        let http_process_stderr_file = paths::get_http_process_stderr_file();
-        let http_server_log_file = paths::get_http_server_log_file();
-        let http_vhost_log_file = paths::get_http_vhost_log_file();
-        let php_process_log_file = paths::get_php_process_log_file();
-        let php_process_err_file = paths::get_php_process_err_file();
-        let php_server_log_file = paths::get_php_server_log_file();
-        let php_server_error_file = paths::get_php_server_error_file();
-
         info!("Tailing following channels:");
-        info!("  {}", rymfony_process_log_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", rymfony_process_err_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", http_process_stdout_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", http_process_stderr_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", http_server_log_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", http_vhost_log_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", php_process_log_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", php_process_err_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", php_server_log_file.file_name().unwrap().to_str().unwrap());
-        info!("  {}", php_server_error_file.file_name().unwrap().to_str().unwrap());
-
-        lines.add_file(rymfony_process_log_file).await.unwrap();
-        lines.add_file(rymfony_process_err_file).await.unwrap();
-
-        lines.add_file(http_process_stdout_file).await.unwrap();
-        lines.add_file(http_process_stderr_file).await.unwrap();
-
-        lines.add_file(http_server_log_file).await.unwrap();
-        lines.add_file(http_vhost_log_file).await.unwrap();
-
-        lines.add_file(php_process_log_file).await.unwrap();
-        lines.add_file(php_process_err_file).await.unwrap();
-
-        lines.add_file(php_server_log_file).await.unwrap();
-        lines.add_file(php_server_error_file).await.unwrap();
-
-        while let Ok(Some(line)) = lines.next_line().await {
-            let source = line.source().file_name().unwrap();
-            println!("[{}] - {}", source.to_str().unwrap().green(), line.line());
+        for log_file in log_files.iter() {
+            info!("  {}", log_file.file_name().unwrap().to_str().unwrap());
+            lines.add_file(log_file).await.unwrap();
+        }
+
+        loop {
+            match tokio::time::timeout(LOG_ENTRY_DELAY, lines.next_line()).await {
+                Ok(Ok(Some(line))) => {
+                    let source = line.source().file_name().unwrap().to_str().unwrap();
+
+                    match parsers.get_mut(source) {
+                        Some(parser) => {
+                            if let Some(record) = parser.push(line.line()) {
+                                print_record(source, record);
+                            }
+                        },
+                        None => println!("[{}] - {}", source.green(), line.line()),
+                    }
+                },
+                Ok(_) => break,
+                Err(_) => {
+                    for (source, parser) in parsers.iter_mut() {
+                        if let Some(record) = parser.flush() {
+                            print_record(source, record);
+                        }
+                    }
+                },
+            }
+        }
+    });
+
+    ExitCode::from(0)
+}
+
+#[cfg(target_family = "windows")]
+fn tail_slow_log(_args: &ArgMatches) -> ExitCode {
+    error!("The slow log is only available with PHP-FPM, which does not exist on Windows.");
+
+    ExitCode::from(1)
+}
+
+/// Displays each entry of the PHP-FPM slow log as a single block, once all its lines are written.
+#[cfg(not(target_family = "windows"))]
+fn tail_slow_log(args: &ArgMatches) -> ExitCode {
+    let slow_log_file = paths::get_php_slow_log_file();
+    let follow = !args.get_flag("no-follow");
+    let entries_count = args.get_one::<String>("lines").and_then(|lines| lines.parse::<usize>().ok()).unwrap_or(0);
+
+    let timeout = slow_log::get_timeout();
+    if timeout.is_none() {
+        warn!("The slow log is disabled, run \"rymfony serve --slow-threshold=<SECONDS>\" to enable it.");
+    }
+
+    let project_dir = get_project_root();
+    let print = |entry: &SlowLogEntry| println!("{}", slow_log::render(entry, timeout.as_deref(), &project_dir));
+
+    // Without following, all entries are displayed by default.
+    let entries = slow_log::parse(&read_to_string(&slow_log_file).unwrap_or_default());
+    let displayed = if follow || entries_count > 0 { entries_count.min(entries.len()) } else { entries.len() };
+    entries[entries.len() - displayed..].iter().for_each(print);
+
+    if !follow {
+        return ExitCode::from(0);
+    }
+
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    rt.block_on(async {
+        let mut lines = MuxedLines::new().unwrap();
+        lines.add_file(&slow_log_file).await.unwrap();
+
+        info!("Waiting for slow requests in {}", slow_log_file.file_name().unwrap().to_str().unwrap());
+
+        let mut parser = SlowLogParser::new();
+
+        loop {
+            match tokio::time::timeout(LOG_ENTRY_DELAY, lines.next_line()).await {
+                Ok(Ok(Some(line))) => parser.push(line.line()).iter().for_each(print),
+                Ok(_) => break,
+                Err(_) => parser.flush().iter().for_each(print),
+            }
         }
     });
 
@@ -1,4 +1,5 @@
 use crate::command_handling::CommandHandler;
+use crate::php::cli;
 use clap::arg;
 use clap::ArgMatches;
 use clap::Command as ClapCommand;
@@ -25,7 +26,7 @@ pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
     let full = args.get_flag("full");
     let initialize_git = !args.get_flag("no-git");
 
-    if directory == "" {
+    if directory.is_empty() {
         let path = env::current_dir().unwrap().join("symfony");
 
         directory = path.to_str().unwrap().to_string();
@@ -34,7 +35,7 @@ pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
     let mut path = PathBuf::from(&directory);
 
     if !path.is_absolute() {
-        path = PathBuf::from(env::current_dir().unwrap()).join(&directory);
+        path = env::current_dir().unwrap().join(&directory);
     }
 
     if path.is_dir() {
@@ -48,11 +49,15 @@ pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
 
     info!("Using directory {}", &path.to_str().unwrap());
 
-    let composer_path = which::which("composer").unwrap();
-
-    info!("Composer detected at {}", &composer_path.to_str().unwrap());
+    let mut command = match cli::get_composer_command() {
+        Ok(command) => command,
+        Err(e) => {
+            error!("Could not create project");
+            error!("{}", e);
 
-    let mut command = Command::new(composer_path.to_str().unwrap());
+            return ExitCode::from(1);
+        },
+    };
 
     command
         .stdin(Stdio::null())
@@ -62,8 +67,13 @@ pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
         .arg(if full { "symfony/website-skeleton" } else { "symfony/skeleton" })
         .arg(path.to_str().unwrap());
 
-    match command.output() {
-        Ok(_) => (),
+    match command.status() {
+        Ok(status) if status.success() => (),
+        Ok(status) => {
+            error!("Could not create project, Composer exited with {}", status);
+
+            return ExitCode::from(1);
+        },
         Err(e) => {
             error!("Could not create project");
             error!("{}", e);
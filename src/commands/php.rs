@@ -0,0 +1,47 @@
+use crate::command_handling::CommandHandler;
+use crate::php::cli;
+use clap::Arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new_passthrough(
+        ClapCommand::new("php")
+            .about("Run the PHP CLI with the project's PHP version")
+            .after_help(
+                "
+All arguments are passed to PHP, and the exit code of PHP is returned.
+
+Example:
+
+$ rymfony php bin/console cache:clear
+",
+            )
+            .disable_help_flag(true)
+            .disable_version_flag(true)
+            .arg(
+                Arg::new("arguments")
+                    .help("The arguments passed to PHP")
+                    .num_args(0..)
+                    .trailing_var_arg(true)
+                    .allow_hyphen_values(true),
+            ),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let arguments: Vec<&String> = args.get_many::<String>("arguments").unwrap_or_default().collect();
+
+    let mut command = match cli::get_php_cli_command() {
+        Ok(command) => command,
+        Err(e) => {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    cli::to_exit_code(command.args(arguments).status())
+}
@@ -0,0 +1,94 @@
+use crate::command_handling::CommandHandler;
+use crate::config::global_config;
+use crate::php::binaries;
+use crate::php::structs::PhpServerSapi;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::env;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("php:add")
+            .about("Add a PHP installation that cannot be discovered automatically")
+            .after_help(
+                "
+The path can be a directory containing PHP binaries, or a PHP binary.
+It is kept in the [discovery] section of the \"~/.rymfony/config.toml\" file,
+so that its binaries are found each time the PHP binaries are discovered.
+
+Examples:
+
+$ rymfony php:add /opt/php83/bin
+$ rymfony php:add /srv/php/bin/php-custom
+",
+            )
+            .arg(arg!(<PATH> "The directory of the PHP binaries, or the path of a PHP binary")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let path = normalize_path(args.get_one::<String>("PATH").unwrap());
+
+    if !path.exists() {
+        error!("\"{}\" does not exist.", path.display());
+
+        return ExitCode::from(1);
+    }
+
+    let found = binaries::probe_path(&path);
+
+    if found.is_empty() {
+        error!("No PHP binary found in \"{}\".", path.display());
+
+        return ExitCode::from(1);
+    }
+
+    for binary in found.iter() {
+        let sapis: Vec<String> = PhpServerSapi::all()
+            .iter()
+            .filter(|sapi| binary.has_sapi(sapi))
+            .map(|sapi| format!("{} ({})", sapi, binary.sapi_path(sapi)))
+            .collect();
+
+        info!("Found PHP {} with {}", binary, sapis.join(", "));
+    }
+
+    let mut paths = global_config::load().discovery.paths;
+    let path = path.to_str().unwrap().to_string();
+
+    if paths.contains(&path) {
+        info!("\"{}\" was already added.", path);
+
+        return ExitCode::from(0);
+    }
+
+    paths.push(path.clone());
+
+    if let Err(e) = global_config::save_discovery_paths(&paths) {
+        error!("Could not save the PHP path: {}", e);
+
+        return ExitCode::from(1);
+    }
+
+    binaries::refresh();
+
+    info!("\"{}\" was added to the PHP paths, run \"rymfony php:list\" to display all PHP binaries.", path);
+
+    ExitCode::from(0)
+}
+
+/// Absolute path, so that it does not depend on the directory the command is executed in.
+pub(crate) fn normalize_path(path: &str) -> PathBuf {
+    let path = env::current_dir().unwrap().join(path);
+
+    // Canonicalize on Windows leaves the "\\?" prefix on canonicalized paths, which causes issues.
+    if cfg!(target_family = "windows") {
+        return path;
+    }
+
+    path.canonicalize().unwrap_or(path)
+}
@@ -0,0 +1,97 @@
+use crate::command_handling::CommandHandler;
+use crate::commands::php_list;
+use crate::php::binaries;
+use crate::utils::table;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use prettytable::format::LinePosition;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("php:info")
+            .about("Display the details of a PHP version")
+            .after_help(
+                "
+The version can also be a constraint, like \"8.2\", and the highest installed version matching it is displayed.
+A build flavor or an installation path can select one of several installations of the same version, like \"8.2 zts\".
+Without argument, the PHP version of the current project is displayed.
+
+Build details are collected when the PHP binaries cache is refreshed, with \"rymfony php:list --refresh\".
+",
+            )
+            .arg(arg!([VERSION] "The PHP version"))
+            .arg(arg!(--format <FORMAT> "The output format").value_parser(["table", "json"]).default_value("table")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let php_binary = match args.get_one::<String>("VERSION") {
+        Some(requested) => {
+            let (constraint, selector) = match binaries::parse_version_selection(requested) {
+                Ok(selection) => selection,
+                Err(e) => {
+                    error!("{}", e);
+
+                    return ExitCode::from(1);
+                },
+            };
+
+            let binaries = binaries::all();
+
+            match binaries::find_best_binary(&binaries, &constraint, &selector) {
+                Some(binary) => binary.clone(),
+                None => {
+                    error!("No installed PHP version matches \"{}\".", requested);
+
+                    return ExitCode::from(1);
+                },
+            }
+        },
+        None => match binaries::get_project_binary() {
+            Some(binary) => binary,
+            None => {
+                error!("No PHP binary found for this project.");
+
+                return ExitCode::from(1);
+            },
+        },
+    };
+
+    if args.get_one::<String>("format").unwrap() == "json" {
+        println!("{}", serde_json::to_string_pretty(&php_list::to_json(&php_binary)).unwrap());
+
+        return ExitCode::from(0);
+    }
+
+    let build = php_binary.build();
+
+    let mut table = table::new_table(LinePosition::Intern);
+
+    table.add_row(row!["Version", php_binary.version().version()]);
+    table.add_row(row!["Build", php_binary.flavor()]);
+    table.add_row(row!["Directory", php_binary.directory()]);
+    table.add_row(row!["PHP CLI", php_binary.cli()]);
+    table.add_row(row!["PHP FPM", php_binary.fpm()]);
+    table.add_row(row!["PHP CGI", php_binary.cgi()]);
+    table.add_row(row!["System", if php_binary.system() { "yes" } else { "no" }]);
+    table.add_row(row!["Source", php_binary.source()]);
+    table.add_row(row![
+        "Thread safety",
+        if build.thread_safe { "enabled (ZTS)" } else { "disabled (NTS)" }
+    ]);
+    table.add_row(row!["Debug build", if build.debug { "yes" } else { "no" }]);
+    table.add_row(row!["Loaded php.ini", build.loaded_ini.as_deref().unwrap_or("(none)")]);
+    table.add_row(row!["Additional .ini files", build.additional_ini.join("\n")]);
+    table.add_row(row!["Extensions", build.extensions.join("\n")]);
+
+    table.printstd();
+
+    if build.extensions.is_empty() {
+        warn!("No build details are known for this binary. Run \"rymfony php:list --refresh\" to collect them.");
+    }
+
+    ExitCode::from(0)
+}
@@ -0,0 +1,109 @@
+use crate::command_handling::CommandHandler;
+use crate::php::cli;
+use crate::php::ini;
+use crate::utils::table;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use prettytable::format::LinePosition;
+use std::collections::BTreeMap;
+use std::process::ExitCode;
+
+// Prints the values of the settings passed as arguments, as a JSON object.
+const PRINT_INI_VALUES_CODE: &str =
+    "$names = array_slice($argv, 1); echo json_encode(array_combine($names, array_map('ini_get', $names)));";
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("php:ini")
+            .about("Display the PHP settings of the project")
+            .after_help(
+                "
+Project settings are read from the \"php.ini\" file at the root of the project,
+and from the [php_ini] section of the \".rymfony.toml\" file, which has precedence:
+
+[php_ini]
+memory_limit = \"512M\"
+upload_max_filesize = \"20M\"
+
+They are applied to the PHP server when it starts.
+Effective values are read from the PHP CLI of the project, with the project settings applied.
+
+You can also display the effective value of any other setting:
+
+$ rymfony php:ini max_execution_time display_errors
+",
+            )
+            .arg(arg!([SETTINGS] ... "Other settings to display")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let settings = ini::get_project_settings();
+
+    let mut names: Vec<String> = settings.iter().map(|setting| setting.name.clone()).collect();
+    for name in args.get_many::<String>("SETTINGS").unwrap_or_default() {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+
+    if names.is_empty() {
+        info!("No PHP setting is defined for this project.");
+
+        return ExitCode::from(0);
+    }
+
+    let effective_values = get_effective_values(&settings, &names).unwrap_or_else(|e| {
+        warn!("Could not read effective values from PHP: {}", e);
+
+        BTreeMap::new()
+    });
+
+    let mut table = table::new_table(LinePosition::Title);
+    table.set_titles(row!["Setting", "Effective value", "Project value", "Source"]);
+
+    for name in names {
+        let setting = settings.iter().find(|setting| setting.name == name);
+
+        let effective_value = match effective_values.get(&name) {
+            Some(serde_json::Value::String(value)) => value.clone(),
+            Some(serde_json::Value::Bool(false)) => String::from("(unknown setting)"),
+            Some(value) => value.to_string(),
+            None => String::from(""),
+        };
+
+        table.add_row(row![
+            name,
+            effective_value,
+            setting.map(|setting| setting.value.as_str()).unwrap_or(""),
+            setting.map(|setting| setting.source).unwrap_or("")
+        ]);
+    }
+
+    table.printstd();
+
+    ExitCode::from(0)
+}
+
+fn get_effective_values(
+    settings: &[ini::IniSetting],
+    names: &[String],
+) -> Result<BTreeMap<String, serde_json::Value>, Box<dyn std::error::Error>> {
+    let mut command = cli::get_php_cli_command()?;
+
+    for setting in settings {
+        command.arg("-d").arg(format!("{}={}", setting.name, setting.value));
+    }
+
+    let output = command.arg("-r").arg(PRINT_INI_VALUES_CODE).arg("--").args(names).output()?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+        return Err(if stderr.is_empty() { format!("PHP exited with {}", output.status) } else { stderr }.into());
+    }
+
+    Ok(serde_json::from_slice(&output.stdout)?)
+}
@@ -2,26 +2,33 @@ use crate::command_handling::CommandHandler;
 use clap::arg;
 use clap::ArgMatches;
 use clap::Command as ClapCommand;
-use prettytable::format;
-use prettytable::Table;
+use prettytable::format::LinePosition;
 use std::process::ExitCode;
 
 use crate::config::config::clear_binaries_list;
 use crate::config::config::save_binaries_to_config;
 use crate::php;
+use crate::php::structs::PhpBinary;
+use crate::utils::table;
+
+const EXTENSIONS_PER_LINE: usize = 10;
 
 pub(crate) fn get_command() -> CommandHandler {
     CommandHandler::new(
         ClapCommand::new("php:list")
         .about("List all available PHP executables.")
         .after_help("
-If you have PHP installed in a custom folder, you can use the RYMFONY_PATH environment variable before executing the command.
+If you have PHP installed in a custom folder, you can add it with the \"php:add\" command, so that it is always discovered.
+You can also use the RYMFONY_PATH environment variable before executing the command.
 
 Example:
 
+$ rymfony php:add /var/php80/bin
 $ RYMFONY_PATH=\"/var/php80/bin\" rymfony php:list --refresh
 ")
         .arg(arg!(-r --refresh "Refresh the PHP list cache"))
+        .arg(arg!(-e --extensions "Display the build and extensions of each binary"))
+        .arg(arg!(--format <FORMAT> "The output format").value_parser(["table", "json"]).default_value("table"))
         ,
         Box::new(execute),
     )
@@ -41,42 +48,63 @@ pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
 
     let binaries = php::binaries::all();
 
-    if binaries.len() == 0 {
-        error!("No PHP installation found. To provide your specific PHP installation path, you can use the RYMFONY_PATH environment variable before running \"rymfony php:list --refresh\".");
+    if binaries.is_empty() {
+        error!("No PHP installation found. To provide your specific PHP installation path, you can run \"rymfony php:add <PATH>\".");
 
         return ExitCode::from(1);
     }
 
     save_binaries_to_config(&binaries);
 
-    let mut table = Table::new();
-    let format = format::FormatBuilder::new()
-        .column_separator('│')
-        .borders('│')
-        .separators(&[format::LinePosition::Top], format::LineSeparator::new('─', '┬', '┌', '┐'))
-        .separators(&[format::LinePosition::Bottom], format::LineSeparator::new('─', '┴', '└', '┘'))
-        .separators(&[format::LinePosition::Title], format::LineSeparator::new('─', '┼', '├', '┤'))
-        .padding(1, 1)
-        .build();
-
-    table.set_format(format);
-    table.set_titles(row!["Version", "PHP CLI", "PHP FPM", "PHP CGI", "System"]);
-
-    let mut ordered_binaries: Vec<_> = binaries.into_iter().collect();
-    ordered_binaries.sort_by(|x, y| x.0.version().cmp(y.0.version()));
-
-    for (php_version, php_binary) in ordered_binaries {
-        let system = if php_binary.system() { "*" } else { "" };
-        table.add_row(row![
-            php_version.version(),
-            php_binary.cli(),
-            php_binary.fpm(),
-            php_binary.cgi(),
-            system
-        ]);
+    let mut ordered_binaries = binaries;
+    ordered_binaries.sort_by(|x, y| x.version().cmp(y.version()).then_with(|| x.directory().cmp(y.directory())));
+
+    if args.get_one::<String>("format").unwrap() == "json" {
+        let binaries: Vec<serde_json::Value> = ordered_binaries.iter().map(to_json).collect();
+        println!("{}", serde_json::to_string_pretty(&binaries).unwrap());
+
+        return ExitCode::from(0);
+    }
+
+    let mut table = table::new_table(LinePosition::Title);
+
+    if args.get_flag("extensions") {
+        table.set_titles(row!["Version", "Build", "Extensions"]);
+
+        for php_binary in ordered_binaries {
+            let build = php_binary.build();
+            let extensions: Vec<String> =
+                build.extensions.chunks(EXTENSIONS_PER_LINE).map(|extensions| extensions.join(", ")).collect();
+
+            table.add_row(row![php_binary.version().version(), php_binary.flavor(), extensions.join("\n")]);
+        }
+    } else {
+        table.set_titles(row!["Version", "Build", "PHP CLI", "PHP FPM", "PHP CGI", "System", "Source"]);
+
+        for php_binary in ordered_binaries {
+            let system = if php_binary.system() { "*" } else { "" };
+            table.add_row(row![
+                php_binary.version().version(),
+                php_binary.flavor(),
+                php_binary.cli(),
+                php_binary.fpm(),
+                php_binary.cgi(),
+                system,
+                php_binary.source()
+            ]);
+        }
     }
 
     table.printstd();
 
     ExitCode::from(0)
 }
+
+/// JSON representation of a binary, shared by the "php:list" and "php:info" commands.
+pub(crate) fn to_json(php_binary: &PhpBinary) -> serde_json::Value {
+    let mut json = serde_json::to_value(php_binary).unwrap();
+    // Fingerprints are only used to invalidate the cache.
+    json.as_object_mut().unwrap().remove("fingerprints");
+
+    json
+}
@@ -0,0 +1,52 @@
+use crate::command_handling::CommandHandler;
+use crate::commands::php_add::normalize_path;
+use crate::config::global_config;
+use crate::php::binaries;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("php:remove")
+            .about("Remove a PHP installation added with \"php:add\"")
+            .arg(arg!(<PATH> "The path given to \"php:add\"")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let requested = args.get_one::<String>("PATH").unwrap();
+    let path = normalize_path(requested);
+    let path = path.to_str().unwrap();
+
+    let mut paths = global_config::load().discovery.paths;
+
+    // The path may not exist anymore, so it is also compared as it was given.
+    let index = match paths.iter().position(|added| added == path || added == requested) {
+        Some(index) => index,
+        None => {
+            error!("\"{}\" was not added with \"php:add\".", requested);
+            if !paths.is_empty() {
+                info!("Added PHP paths: {}", paths.join(", "));
+            }
+
+            return ExitCode::from(1);
+        },
+    };
+
+    let removed = paths.remove(index);
+
+    if let Err(e) = global_config::save_discovery_paths(&paths) {
+        error!("Could not save the PHP paths: {}", e);
+
+        return ExitCode::from(1);
+    }
+
+    binaries::refresh();
+
+    info!("\"{}\" was removed from the PHP paths.", removed);
+
+    ExitCode::from(0)
+}
@@ -0,0 +1,293 @@
+use crate::command_handling::CommandHandler;
+use crate::commands::stop;
+use crate::config::paths;
+use crate::php::binaries;
+use crate::php::binaries::PHP_VERSION_FILE;
+use crate::php::structs::PhpBinary;
+use crate::php::structs::PhpServerSapi;
+use crate::php::xdebug;
+use crate::utils::current_process_name;
+use crate::utils::project_directory::get_project_root;
+use crate::utils::prompt;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::fs::read_to_string;
+use std::fs::write;
+use std::process::Command;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("php:use")
+            .about("Select the PHP version used by the current project")
+            .after_help(
+                "
+The selected version is written to the \".php-version\" file of the project.
+
+Without argument, you will be asked to pick one of the installed PHP versions.
+The version can also be a constraint, like \"8.2\" or \"^8.1\", and the highest installed version matching it will be used.
+When several installations of the same version exist, one of them can be selected with its build flavor,
+like \"8.2 zts\" or \"8.2 debug\", or with its path, like \"/opt/php-debug\".
+
+Examples:
+
+$ rymfony php:use
+$ rymfony php:use 8.2
+$ rymfony php:use \"8.2 debug\"
+",
+            )
+            .arg(arg!([VERSION] "The PHP version or constraint to use")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let binaries = binaries::all();
+
+    if binaries.is_empty() {
+        error!(
+            "No PHP installation found. You can execute \"rymfony php:list --refresh\" to update binaries paths cache."
+        );
+
+        return ExitCode::from(1);
+    }
+
+    let (binary, pinned_version) = match args.get_one::<String>("VERSION") {
+        Some(requested) => match find_requested_binary(&binaries, requested) {
+            Some(binary) => (binary, requested.clone()),
+            None => return ExitCode::from(1),
+        },
+        None => {
+            if !prompt::is_interactive() {
+                error!("Please specify the PHP version to use, like \"rymfony php:use 8.2\".");
+
+                return ExitCode::from(1);
+            }
+
+            let binary = match ask_for_binary(&binaries) {
+                Some(binary) => binary,
+                None => return ExitCode::from(1),
+            };
+            let pinned_version = get_pinned_version(&binaries, binary);
+
+            (binary, pinned_version)
+        },
+    };
+
+    match binary.server_sapi() {
+        None => {
+            error!("PHP {} cannot be used: no PHP binary was found for this version.", binary);
+
+            return ExitCode::from(1);
+        },
+        Some(PhpServerSapi::CLI) => warn!(
+            "PHP {} has no FPM or CGI binary: \"rymfony serve\" will use PHP's built-in web server, which handles one request at a time.",
+            binary
+        ),
+        Some(_) => {},
+    }
+
+    if let Err(e) = write(get_project_root().join(PHP_VERSION_FILE), format!("{}\n", pinned_version)) {
+        error!("Could not write the \"{}\" file: {}", PHP_VERSION_FILE, e);
+
+        return ExitCode::from(1);
+    }
+
+    info!("PHP {} is now used for this project (\"{}\" in \"{}\").", binary, pinned_version, PHP_VERSION_FILE);
+
+    if let Some(composer_constraint) = binaries::get_composer_version_constraint() {
+        if !composer_constraint.constraint.matches(binary.version().version()) {
+            warn!(
+                "PHP {} does not match the \"{}\" constraint from {}.",
+                binary.version().version(),
+                composer_constraint.raw,
+                composer_constraint.source
+            );
+        }
+    }
+
+    if paths::rymfony_pid_file().exists()
+        && prompt::confirm("A server is running for this project. Do you want to restart it?", false)
+    {
+        return restart_server();
+    }
+
+    ExitCode::from(0)
+}
+
+fn find_requested_binary<'a>(binaries: &'a [PhpBinary], requested: &str) -> Option<&'a PhpBinary> {
+    let (constraint, selector) = match binaries::parse_version_selection(requested) {
+        Ok(selection) => selection,
+        Err(e) => {
+            error!("{}", e);
+
+            return None;
+        },
+    };
+
+    let binary = binaries::find_best_binary(binaries, &constraint, &selector);
+
+    if binary.is_none() {
+        error!("No installed PHP version matches \"{}\".", requested);
+        error!("You can execute \"rymfony php:list --refresh\" to update binaries paths cache.");
+    }
+
+    binary
+}
+
+/// Returns nothing when the input is closed before a version is selected.
+fn ask_for_binary(binaries: &[PhpBinary]) -> Option<&PhpBinary> {
+    let mut ordered_binaries: Vec<&PhpBinary> = binaries.iter().collect();
+    ordered_binaries.sort_by(|x, y| x.version().cmp(y.version()).then_with(|| x.directory().cmp(y.directory())));
+
+    let current_binary = binaries::get_project_version_constraint().and_then(|project_constraint| {
+        binaries::find_best_binary(binaries, &project_constraint.constraint, &project_constraint.selector)
+    });
+
+    println!("Installed PHP versions:");
+
+    for (index, binary) in ordered_binaries.iter().enumerate() {
+        let sapis: Vec<String> = ["CLI", "FPM", "CGI"]
+            .iter()
+            .zip([binary.cli(), binary.fpm(), binary.cgi()])
+            .filter(|(_, path)| !path.is_empty())
+            .map(|(sapi, _)| sapi.to_string())
+            .collect();
+        let is_current = current_binary.is_some_and(|current| current.is_same_installation(binary));
+        let current = if is_current { " (current)" } else { "" };
+
+        println!(
+            "  [{}] {} {} ({}) in {}{}",
+            index + 1,
+            binary.version().version(),
+            binary.flavor(),
+            sapis.join(", "),
+            binary.directory(),
+            current
+        );
+    }
+
+    loop {
+        let answer = prompt::ask("Which PHP version do you want to use?")?;
+
+        match answer.parse::<usize>() {
+            Ok(number) if number >= 1 && number <= ordered_binaries.len() => return Some(ordered_binaries[number - 1]),
+            _ => println!("Please enter a number between 1 and {}.", ordered_binaries.len()),
+        }
+    }
+}
+
+/// Pins the minor version only, unless it would select a higher patch version than the chosen one.
+/// When other installations of the same version exist, the build flavor is added,
+/// or the installation path is pinned if the flavor is not enough to select it.
+fn get_pinned_version(binaries: &[PhpBinary], binary: &PhpBinary) -> String {
+    let version = binary.version();
+    let selects = |pinned: &str| {
+        let (constraint, selector) = binaries::parse_version_selection(pinned).unwrap();
+        binaries::find_best_binary(binaries, &constraint, &selector)
+    };
+
+    let minor_version = format!("{}.{}", version.major(), version.minor());
+    let pinned_version = if selects(&minor_version).map(|selected| selected.version()) == Some(version) {
+        minor_version
+    } else {
+        version.version().to_string()
+    };
+
+    if selects(&pinned_version).is_some_and(|selected| selected.is_same_installation(binary)) {
+        return pinned_version;
+    }
+
+    let pinned_flavor = format!("{} {}", pinned_version, binary.flavor().to_lowercase());
+    if selects(&pinned_flavor).is_some_and(|selected| selected.is_same_installation(binary)) {
+        return pinned_flavor;
+    }
+
+    binary.directory().clone()
+}
+
+fn restart_server() -> ExitCode {
+    let serve_arguments: Vec<String> = read_to_string(paths::get_serve_arguments_file())
+        .ok()
+        .and_then(|arguments| serde_json::from_str(&arguments).ok())
+        .unwrap_or_default();
+    // Read before stopping the server, which forgets the mode set by "server:xdebug".
+    let serve_arguments = with_xdebug_mode(serve_arguments, xdebug::get_mode());
+
+    stop::stop_server();
+
+    info!("Restarting the server in the background...");
+
+    let status =
+        Command::new(current_process_name::get().as_str()).arg("serve").args(serve_arguments).arg("--daemon").status();
+
+    match status {
+        Ok(status) if status.success() => ExitCode::from(0),
+        Ok(_) => {
+            error!("The server could not be restarted. Run \"rymfony serve\" to start it again.");
+
+            ExitCode::from(1)
+        },
+        Err(e) => {
+            error!("The server could not be restarted: {}", e);
+
+            ExitCode::from(1)
+        },
+    }
+}
+
+/// Replaces the Xdebug mode the server was started with by its current one.
+fn with_xdebug_mode(serve_arguments: Vec<String>, mode: Option<String>) -> Vec<String> {
+    let mut serve_arguments: Vec<String> =
+        serve_arguments.into_iter().filter(|argument| !argument.starts_with("--xdebug")).collect();
+
+    if let Some(mode) = mode {
+        serve_arguments.push(format!("--xdebug={}", mode));
+    }
+
+    serve_arguments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::php::structs::PhpVersion;
+
+    fn binaries(installations: &[(&str, &str, &str)]) -> Vec<PhpBinary> {
+        installations
+            .iter()
+            .map(|(version, directory, flavor)| {
+                PhpBinary::new(version.parse::<PhpVersion>().unwrap(), directory, flavor)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn pinned_version_is_minor_unless_a_higher_patch_exists() {
+        let binaries = binaries(&[
+            ("8.1.2", "/usr", "NTS"),
+            ("8.1.27", "/usr/local", "NTS"),
+            ("8.2.10", "/usr", "NTS"),
+            ("8.2.10", "/opt/php-debug", "NTS debug"),
+            ("8.2.10", "/opt/php-debug-2", "NTS debug"),
+        ]);
+
+        assert_eq!(get_pinned_version(&binaries, &binaries[1]), "8.1");
+        assert_eq!(get_pinned_version(&binaries, &binaries[0]), "8.1.2");
+        assert_eq!(get_pinned_version(&binaries, &binaries[2]), "8.2");
+        assert_eq!(get_pinned_version(&binaries, &binaries[3]), "8.2 nts debug");
+        assert_eq!(get_pinned_version(&binaries, &binaries[4]), "/opt/php-debug-2");
+    }
+
+    #[test]
+    fn restarted_server_keeps_the_current_xdebug_mode() {
+        let arguments = vec![String::from("--port"), String::from("8000"), String::from("--xdebug=debug")];
+
+        assert_eq!(
+            with_xdebug_mode(arguments.clone(), Some(String::from("develop,trace"))),
+            ["--port", "8000", "--xdebug=develop,trace"]
+        );
+        assert_eq!(with_xdebug_mode(arguments, None), ["--port", "8000"]);
+    }
+}
@@ -0,0 +1,116 @@
+use crate::command_handling::CommandHandler;
+use crate::config::projects;
+use crate::utils::project_directory::canonical_project_path;
+use crate::utils::project_directory::project_hash;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use dirs::home_dir;
+use std::fs::read_to_string;
+use std::fs::rename;
+use std::fs::write;
+use std::path::Path;
+use std::process::ExitCode;
+
+// Config files of the Rymfony directory containing its own path, like the paths of the PHP-FPM logs.
+const FILES_WITH_DIRECTORY_PATH: [&str; 1] = ["fpm-conf.ini"];
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("project:move")
+            .about("Keep the Rymfony directory of a project after it was moved")
+            .after_help(
+                "
+Each project has a directory in \"~/.rymfony\", containing its logs, configs and Caddy binary,
+which is named after the path of the project.
+After moving or renaming a project, this command makes it use its previous directory again.
+
+Example:
+
+$ mv ~/dev/my-app ~/projects/my-app
+$ rymfony project:move ~/dev/my-app ~/projects/my-app
+",
+            )
+            .arg(arg!(<OLD_PATH> "The previous path of the project"))
+            .arg(arg!(<NEW_PATH> "The new path of the project")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let old_path = canonical_project_path(Path::new(args.get_one::<String>("OLD_PATH").unwrap()));
+    let new_path = canonical_project_path(Path::new(args.get_one::<String>("NEW_PATH").unwrap()));
+
+    if !new_path.is_dir() {
+        error!("\"{}\" is not a directory.", new_path.display());
+
+        return ExitCode::from(1);
+    }
+
+    let rymfony_home = home_dir().unwrap().join(".rymfony");
+    let (old_hash, new_hash) = (project_hash(&old_path), project_hash(&new_path));
+    let (old_directory, new_directory) = (rymfony_home.join(&old_hash), rymfony_home.join(&new_hash));
+
+    if old_hash == new_hash {
+        info!("Both paths already use the same Rymfony directory.");
+
+        return ExitCode::from(0);
+    }
+
+    if !old_directory.is_dir() {
+        error!("No Rymfony directory was found for \"{}\".", old_path.display());
+
+        return ExitCode::from(1);
+    }
+    if old_directory.join(".rymfony.pid").exists() {
+        error!("The server of \"{}\" is running, stop it before moving the project.", old_path.display());
+
+        return ExitCode::from(1);
+    }
+    if new_directory.exists() {
+        error!(
+            "\"{}\" already has a Rymfony directory, remove it before moving the project: {}",
+            new_path.display(),
+            new_directory.display()
+        );
+
+        return ExitCode::from(1);
+    }
+
+    if let Err(e) = rename(&old_directory, &new_directory) {
+        error!("Could not move \"{}\": {}", old_directory.display(), e);
+
+        return ExitCode::from(1);
+    }
+
+    replace_directory_path(&new_directory, &old_directory);
+
+    let mut registry = projects::load();
+    let previous_entry = registry.remove(&old_hash);
+    projects::register(&mut registry, &new_hash, &new_path);
+    if let Some(previous_entry) = previous_entry {
+        registry.get_mut(&new_hash).unwrap().created_at = previous_entry.created_at;
+    }
+    if let Err(e) = projects::save(&registry) {
+        warn!("Could not update the projects registry: {}", e);
+    }
+
+    info!("The Rymfony directory of \"{}\" is now used by \"{}\".", old_path.display(), new_path.display());
+
+    ExitCode::from(0)
+}
+
+fn replace_directory_path(directory: &Path, old_directory: &Path) {
+    for file_name in FILES_WITH_DIRECTORY_PATH {
+        let file = directory.join(file_name);
+        let content = match read_to_string(&file) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        let content = content.replace(old_directory.to_str().unwrap(), directory.to_str().unwrap());
+        if let Err(e) = write(&file, content) {
+            warn!("Could not update \"{}\": {}", file.display(), e);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use crate::command_handling::CommandHandler;
+use crate::http::access_log::get_captured_requests;
+use crate::http::har::Har;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::fs::write;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("server:requests:export")
+            .about("Export the HTTP requests handled by the server")
+            .after_help(
+                "
+Requests are read from the HTTP server's access log of the current project.
+
+The HAR format can be imported in browsers' devtools, and most HTTP debugging tools.
+Request and response bodies are not part of the access log, so they are not exported.
+",
+            )
+            .arg(arg!(--format <FORMAT> "The export format").value_parser(["har"]).default_value("har"))
+            .arg(arg!(-o --output <FILE> "Write the export to this file instead of the standard output")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let requests = match get_captured_requests() {
+        Ok(requests) => requests,
+        Err(e) => {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    let export = serde_json::to_string_pretty(&Har::from_requests(&requests)).expect("Could not serialize HAR export.");
+
+    match args.get_one::<String>("output") {
+        Some(output) => {
+            if let Err(e) = write(output, export) {
+                error!("Could not write export to \"{}\": {}", output, e);
+
+                return ExitCode::from(1);
+            }
+            info!("Exported {} request(s) to {}", requests.len(), output);
+        },
+        None => println!("{}", export),
+    }
+
+    ExitCode::from(0)
+}
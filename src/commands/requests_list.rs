@@ -0,0 +1,57 @@
+use crate::command_handling::CommandHandler;
+use crate::http::access_log::get_captured_requests;
+use crate::utils::table;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use prettytable::format::LinePosition;
+use std::process::ExitCode;
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("server:requests:list")
+            .alias("server:requests")
+            .about("List the HTTP requests handled by the server")
+            .after_help(
+                "
+Requests are read from the HTTP server's access log of the current project.
+The IDs displayed here can be used with the \"server:requests:replay\" command.
+",
+            ),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(_args: &ArgMatches) -> ExitCode {
+    let requests = match get_captured_requests() {
+        Ok(requests) => requests,
+        Err(e) => {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    if requests.is_empty() {
+        info!("No request was captured yet.");
+
+        return ExitCode::from(0);
+    }
+
+    let mut table = table::new_table(LinePosition::Title);
+    table.set_titles(row!["ID", "Date", "Method", "URL", "Status", "Duration"]);
+
+    for request in requests {
+        table.add_row(row![
+            request.id,
+            request.date(),
+            request.request.method,
+            request.url(),
+            request.status,
+            format!("{:.1} ms", request.duration * 1000.0)
+        ]);
+    }
+
+    table.printstd();
+
+    ExitCode::from(0)
+}
@@ -0,0 +1,283 @@
+use crate::command_handling::CommandHandler;
+use crate::http::access_log::get_captured_request;
+use crate::http::access_log::CapturedRequest;
+use crate::http::access_log::REDACTED_HEADER_VALUE;
+use clap::arg;
+use clap::value_parser;
+use clap::ArgAction;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use colored::*;
+use std::collections::BTreeMap;
+use std::process::Command;
+use std::process::ExitCode;
+use std::process::Stdio;
+
+#[cfg(target_family = "windows")]
+const NULL_DEVICE: &str = "NUL";
+
+#[cfg(not(target_family = "windows"))]
+const NULL_DEVICE: &str = "/dev/null";
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("server:requests:replay")
+            .about("Send a captured HTTP request again, and compare the responses")
+            .after_help(
+                "
+Use \"server:requests:list\" to find the ID of the request to replay.
+
+Headers can be edited with the --header option:
+ * \"Name: value\" replaces (or adds) a header,
+ * \"Name:\" removes it.
+
+Request bodies are not part of the access log: if the original request had one, use --body to send it.
+Prefix the value with \"@\" to read the body from a file, like \"--body @payload.json\".
+
+This command requires \"curl\" to be installed.
+",
+            )
+            .arg(arg!(<id> "The ID of the captured request").value_parser(value_parser!(usize)))
+            .arg(
+                arg!(-H --header <HEADER> "Edit a request header, like \"Accept: application/json\"")
+                    .action(ArgAction::Append),
+            )
+            .arg(arg!(--body <BODY> "The request body to send")),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    let id = *args.get_one::<usize>("id").unwrap();
+
+    let original = match get_captured_request(id) {
+        Ok(request) => request,
+        Err(e) => {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    let curl_path = match which::which("curl") {
+        Ok(path) => path,
+        Err(_) => {
+            error!("Could not find \"curl\", which is necessary to replay requests.");
+
+            return ExitCode::from(1);
+        },
+    };
+
+    let header_edits: Vec<String> = args.get_many::<String>("header").unwrap_or_default().cloned().collect();
+    let body = args.get_one::<String>("body");
+
+    let headers = match get_replay_headers(&original, &header_edits, body.is_some()) {
+        Ok(headers) => headers,
+        Err(e) => {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    if body.is_none() && !matches!(original.request.method.as_str(), "GET" | "HEAD" | "OPTIONS" | "DELETE") {
+        warn!("The original request body was not captured, the request will be replayed without a body.");
+    }
+
+    info!("Replaying request #{}: {} {}", id, original.request.method, original.url());
+
+    let mut command = Command::new(curl_path);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .arg("--silent")
+        .arg("--show-error")
+        .arg("--insecure") // Local certificates may not be trusted by curl
+        .arg("--request")
+        .arg(&original.request.method)
+        .arg("--dump-header")
+        .arg("-")
+        .arg("--output")
+        .arg(NULL_DEVICE)
+        .arg("--write-out")
+        .arg("%{size_download}");
+
+    for (name, values) in headers.values() {
+        for value in values {
+            command.arg("--header").arg(format!("{}: {}", name, value));
+        }
+    }
+
+    if let Some(body) = body {
+        command.arg("--data-binary").arg(body);
+    }
+
+    command.arg(original.url());
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => {
+            error!("Could not execute curl: {}", e);
+
+            return ExitCode::from(1);
+        },
+    };
+
+    if !output.status.success() {
+        error!("Could not replay request: {}", String::from_utf8_lossy(&output.stderr).trim());
+
+        return ExitCode::from(1);
+    }
+
+    let replayed = parse_curl_output(&String::from_utf8_lossy(&output.stdout));
+
+    print_diff(&original, &replayed);
+
+    ExitCode::from(0)
+}
+
+struct ReplayedResponse {
+    status: u16,
+    size: i64,
+    headers: Vec<(String, String)>,
+}
+
+/// Headers are keyed by their lowercased name, because HTTP/2 responses and Caddy logs don't use the same case.
+/// Repeated headers, like several "Cookie" lines, keep all their values.
+fn get_replay_headers(
+    original: &CapturedRequest,
+    edits: &[String],
+    has_new_body: bool,
+) -> Result<BTreeMap<String, (String, Vec<String>)>, String> {
+    let mut headers: BTreeMap<String, (String, Vec<String>)> = BTreeMap::new();
+
+    for (name, values) in original.request.headers.iter() {
+        let lowercase_name = name.to_lowercase();
+        if has_new_body && lowercase_name == "content-length" {
+            continue;
+        }
+        for value in values {
+            if value == REDACTED_HEADER_VALUE {
+                warn!("Header \"{}\" was redacted in the access log, it will not be sent.", name);
+                continue;
+            }
+            headers.entry(lowercase_name.clone()).or_insert_with(|| (name.clone(), Vec::new())).1.push(value.clone());
+        }
+    }
+
+    for edit in edits {
+        let (name, value) =
+            edit.split_once(':').ok_or(format!("Invalid header \"{}\", expected \"Name: value\".", edit))?;
+        let (name, value) = (name.trim(), value.trim());
+
+        if value.is_empty() {
+            headers.remove(&name.to_lowercase());
+        } else {
+            headers.insert(name.to_lowercase(), (name.to_string(), vec![value.to_string()]));
+        }
+    }
+
+    Ok(headers)
+}
+
+fn parse_curl_output(output: &str) -> ReplayedResponse {
+    // Output contains all header blocks (one per response, like with "100 Continue"),
+    // followed by the "--write-out" content, which is the downloaded size.
+    let output = output.replace("\r\n", "\n");
+    let (header_blocks, size) = output.rsplit_once('\n').unwrap_or(("", output.as_str()));
+
+    let last_block = header_blocks.split("\n\n").filter(|block| !block.trim().is_empty()).last().unwrap_or("");
+    let mut lines = last_block.lines();
+
+    let status = lines
+        .next()
+        .and_then(|status_line| status_line.split_whitespace().nth(1))
+        .and_then(|status| status.parse().ok())
+        .unwrap_or(0);
+
+    let headers = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    ReplayedResponse { status, size: size.trim().parse().unwrap_or(0), headers }
+}
+
+fn print_diff(original: &CapturedRequest, replayed: &ReplayedResponse) {
+    if original.status == replayed.status {
+        println!("  Status: {}", original.status);
+    } else {
+        println!("  Status: {} → {}", original.status.to_string().red(), replayed.status.to_string().green());
+    }
+
+    if original.size == replayed.size {
+        println!("  Size:   {} bytes", original.size);
+    } else {
+        println!("  Size:   {} → {} bytes", original.size.to_string().red(), replayed.size.to_string().green());
+    }
+
+    let original_headers: Vec<(String, String)> = original
+        .resp_headers
+        .iter()
+        .flat_map(|(name, values)| values.iter().map(move |value| (name.clone(), value.clone())))
+        .collect();
+
+    let is_in = |headers: &[(String, String)], (name, value): &(String, String)| {
+        headers.iter().any(|(n, v)| n.eq_ignore_ascii_case(name) && v == value)
+    };
+
+    let mut has_diff = false;
+    println!("  Headers:");
+    for header in original_headers.iter() {
+        if !is_in(&replayed.headers, header) {
+            has_diff = true;
+            println!("{}", format!("  - {}: {}", header.0, header.1).red());
+        }
+    }
+    for header in replayed.headers.iter() {
+        if !is_in(&original_headers, header) {
+            has_diff = true;
+            println!("{}", format!("  + {}: {}", header.0, header.1).green());
+        }
+    }
+    if !has_diff {
+        println!("    (identical)");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_curl_output_keeps_last_response() {
+        let output = "HTTP/1.1 100 Continue\r\n\r\nHTTP/2 404\r\ncontent-type: text/html\r\nserver: Caddy\r\n\r\n1234";
+
+        let response = parse_curl_output(output);
+
+        assert_eq!(response.status, 404);
+        assert_eq!(response.size, 1234);
+        assert_eq!(
+            response.headers,
+            vec![
+                ("content-type".to_string(), "text/html".to_string()),
+                ("server".to_string(), "Caddy".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_request_headers_are_all_replayed() {
+        let original: CapturedRequest = serde_json::from_str(
+            r#"{"ts":1672531200.5,"request":{"method":"GET","host":"localhost","uri":"/","headers":{"Accept":["text/html","application/json"],"Cookie":["a=1","b=2"],"Content-Length":["12"]}}}"#,
+        )
+        .unwrap();
+
+        let headers = get_replay_headers(&original, &[String::from("Accept: */*")], true).unwrap();
+
+        assert_eq!(headers["cookie"], (String::from("Cookie"), vec![String::from("a=1"), String::from("b=2")]));
+        assert_eq!(headers["accept"], (String::from("Accept"), vec![String::from("*/*")]));
+        assert!(!headers.contains_key("content-length"));
+    }
+}
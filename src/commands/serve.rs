@@ -1,4 +1,3 @@
-use std::env;
 use std::fs::write;
 use std::fs::File;
 use std::fs::OpenOptions;
@@ -11,15 +10,23 @@ use std::time::Duration;
 
 use crate::command_handling::CommandHandler;
 use crate::config::paths;
+use crate::config::project_config;
+use crate::http::error_pages;
 use crate::http::proxy_server;
 use crate::http::proxy_server::start_caddy;
-use crate::php::php_server;
-use crate::php::php_server::start_php_server;
-use crate::php::php_server::PhpServerStartInput;
+use crate::php::backend;
+#[cfg(not(target_family = "windows"))]
+use crate::php::fpm_pool;
+#[cfg(not(target_family = "windows"))]
+use crate::php::server_fpm;
+use crate::php::xdebug;
 use crate::utils::current_process_name;
+use crate::utils::file_watcher::FileWatcher;
 use crate::utils::network::find_available_port;
 use crate::utils::network::parse_default_port;
+use crate::utils::project_directory::get_project_root;
 use crate::utils::project_directory::get_rymfony_project_directory;
+use crate::utils::project_directory::reset_rymfony_config_files;
 use clap::arg;
 use clap::ArgMatches;
 use clap::Command as ClapCommand;
@@ -29,6 +36,8 @@ use sysinfo::get_current_pid;
 const DEFAULT_PORT: &str = "8000";
 const DEFAULT_HOST: &str = "127.0.0.1";
 
+const HEALTHCHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 pub(crate) fn get_command() -> CommandHandler {
     CommandHandler::new(
         ClapCommand::new("server:start")
@@ -37,9 +46,11 @@ pub(crate) fn get_command() -> CommandHandler {
             .about("Runs an HTTP server")
             .after_help(
                 "
-Runs an HTTP server and a PHP server (based on FPM or CGI depending on what's available).
+Runs an HTTP server and a PHP server (based on FPM, CGI or PHP's built-in web server, depending on what's available).
+Use \"--php-address\" to send PHP requests to a FastCGI server that Rymfony does not manage, like PHP-FPM in a container.
+Use \"--watch\" to reload PHP when files like \".env\" or \"composer.lock\" change, see the \"[watch]\" table of \".rymfony.toml\".
 
-Rymfony is capable of detecting your Document Root automatically.
+Rymfony is capable of detecting your Document Root automatically, in the project root directory.
 It will do so in this order:
  * ./public/
  * ./web/
@@ -56,13 +67,44 @@ It will do so in this order:
             .arg(arg!(-d --daemon "Run the server in the background"))
             .arg(arg!(--"document-root" <DIRECTORY_PATH> "Project's document root"))
             .arg(arg!(--passthru <ENTRYPOINT> "The PHP entrypoint all requests will be passed to"))
+            .arg(
+                arg!(--"php-backend" <BACKEND> "The PHP server to use, the best available one by default")
+                    .value_parser(backend::BACKEND_NAMES),
+            )
+            .arg(arg!(--"php-address" <ADDRESS> "Address of an external PHP FastCGI server, as \"host:port\" or a socket path"))
             .arg(arg!(--"no-tls" "Disable TLS. Use HTTP only."))
-            .arg(arg!(-s --"expose-server-header" "Add server header into all response")),
+            .arg(arg!(--watch "Reload the PHP server when watched files of the project change"))
+            .arg(arg!(--"reset-config" "Create the PHP-FPM and Caddy configs again from Rymfony's templates"))
+            .arg(arg!(-s --"expose-server-header" "Add server header into all response"))
+            .arg(
+                arg!(--"slow-threshold" <SECONDS> "Log the backtrace of PHP requests slower than this, in the \"slow\" logs channel (PHP-FPM only)")
+                    .value_parser(clap::value_parser!(u32).range(1..)),
+            )
+            .arg(
+                arg!(--xdebug [MODE] "Enable Xdebug in the PHP server, with the \"debug\" mode by default")
+                    .require_equals(true)
+                    .default_missing_value(xdebug::DEFAULT_MODE),
+            ),
         Box::new(execute),
     )
 }
 
 pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    // Done before starting a background server, so that its restarts keep the user's changes.
+    if args.get_flag("reset-config") {
+        if paths::rymfony_pid_file().exists() {
+            error!("The server is running for this directory, stop it before resetting its config.");
+
+            return ExitCode::from(1);
+        }
+
+        reset_rymfony_config_files();
+    }
+
+    // Also done before starting a background server, which cannot ask questions.
+    #[cfg(not(target_family = "windows"))]
+    server_fpm::check_config_template();
+
     if args.get_flag("daemon") {
         serve_background(args)
     } else {
@@ -113,30 +155,64 @@ fn serve_foreground(args: &ArgMatches) -> ExitCode {
     };
 
     let php_entrypoint_path = doc_root_path.join(script_filename.as_str());
-    let (mut php_start_command, php_server_input) = if !php_entrypoint_path.is_file() {
+    if !php_entrypoint_path.is_file() {
         error!("No PHP entrypoint specified.");
 
         return ExitCode::from(1);
-    } else {
-        php_server::get_php_server_start_input()
+    }
+
+    let xdebug_mode = args.get_one::<String>("xdebug");
+    if let Some(mode) = xdebug_mode {
+        if let Err(e) = xdebug::validate_mode(mode) {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        }
+    }
+    xdebug::set_mode(xdebug_mode.map(|mode| mode.as_str()));
+
+    #[cfg(not(target_family = "windows"))]
+    if let Some(seconds) = args.get_one::<u32>("slow-threshold") {
+        fpm_pool::set_slow_threshold(*seconds);
+    }
+
+    let php_backend_name = args.get_one::<String>("php-backend").map(|s| s.as_str()).unwrap_or("auto");
+    let php_address = args.get_one::<String>("php-address").map(|s| s.as_str());
+
+    let mut php_backend = match backend::create(php_backend_name, php_address, &document_root, &script_filename) {
+        Ok(php_backend) => php_backend,
+        Err(e) => {
+            error!("{}", e);
+            xdebug::set_mode(None);
+
+            return ExitCode::from(1);
+        },
     };
 
-    let sapi = php_server_input.sapi;
-    let sapi_string = sapi.to_string();
+    info!("PHP starting with the {} backend", php_backend.name());
+    info!("PHP entrypoint file: {}", &script_filename);
+    if let Some(mode) = xdebug_mode {
+        info!("Xdebug enabled with mode \"{}\"", mode);
+    }
 
-    if sapi_string == "unknown" {
-        error!("Unknown PHP SAPI to execute");
+    if let Err(e) = php_backend.start() {
+        error!("{}", e);
+        xdebug::set_mode(None);
 
         return ExitCode::from(1);
-    } else {
-        info!("PHP starting with module {}", sapi_string);
-        info!("PHP entrypoint file: {}", &script_filename);
     }
 
-    let php_port = php_server_input.port.clone();
-    let php_bin = php_server_input.php_bin.clone();
+    error_pages::watch_php_backend(php_backend.as_ref());
+
+    let project_root = get_project_root();
+    let mut file_watcher = if args.get_flag("watch") {
+        let watcher = FileWatcher::new(&project_root, project_config::load().watch.files());
+        info!("Watching {} to reload PHP", watcher.patterns().join(", "));
 
-    let mut php_process = start_php_server(&mut php_start_command, php_server_input.clone());
+        Some(watcher)
+    } else {
+        None
+    };
 
     info!("Starting Caddy HTTP server...");
 
@@ -151,6 +227,9 @@ fn serve_foreground(args: &ArgMatches) -> ExitCode {
 
     write(&rymfony_pid_file, rymfony_pid.to_string()).expect("Could not write Rymfony PID to file.");
 
+    let serve_arguments = serde_json::to_string(&serve_arguments(args, http_port)).unwrap();
+    write(paths::get_serve_arguments_file(), serve_arguments).expect("Could not write server arguments to file.");
+
     //Serialize
     let no_tls = args.get_flag("no-tls");
 
@@ -161,12 +240,12 @@ fn serve_foreground(args: &ArgMatches) -> ExitCode {
     let (mut caddy_command, caddy_command_input) = proxy_server::get_caddy_start_command(
         !no_tls,
         host_name.clone(),
-        http_port.clone(),
-        php_port.clone(),
+        http_port,
+        php_backend.as_ref(),
         document_root,
         script_filename,
         args.get_flag("expose-server-header"),
-        if verbosity_level == 3 { true } else { false },
+        verbosity_level == 3,
     );
 
     let mut caddy_process = start_caddy(&mut caddy_command, caddy_command_input.config.clone());
@@ -187,22 +266,23 @@ fn serve_foreground(args: &ArgMatches) -> ExitCode {
     let error: Option<String> = loop {
         let caddy_command_input = caddy_command_input.clone();
 
-        std::thread::sleep(Duration::from_secs(100));
+        std::thread::sleep(HEALTHCHECK_INTERVAL);
 
         //
         // PHP server healthcheck
         //
-        let php_server_input = PhpServerStartInput { sapi, port: php_port, php_bin: php_bin.clone() };
-        let php_process_status = php_process.try_wait();
-        match php_process_status {
-            Ok(Some(status)) => {
-                debug!("PHP stopped with exit code {}. Restarting it.", status.code().unwrap_or(255));
-                php_process = start_php_server(&mut php_start_command, php_server_input);
-                debug!("PHP restarted, running with PID {}", php_process.id());
-            },
-            Ok(None) => (), // PHP server still alive.
-            Err(e) => break Some(format!("An error occured when checking PHP server health: {:?}", e).into()),
-        };
+        if let Err(e) = php_backend.check_health() {
+            break Some(format!("An error occured when checking PHP server health: {:?}", e));
+        }
+
+        if let Some(changed_file) = file_watcher.as_mut().and_then(|watcher| watcher.poll()) {
+            let changed_file = changed_file.strip_prefix(&project_root).unwrap_or(&changed_file).to_path_buf();
+            info!("\"{}\" changed, reloading {}", changed_file.display(), php_backend.name());
+
+            if let Err(e) = php_backend.reload() {
+                error!("Could not reload PHP, it keeps running with its previous config: {}", e);
+            }
+        }
 
         //
         // HTTP server healthcheck
@@ -215,12 +295,13 @@ fn serve_foreground(args: &ArgMatches) -> ExitCode {
                 debug!("Caddy restarted, running with PID {}", caddy_process.id());
             },
             Ok(None) => (), // HTTP server still alive.
-            Err(e) => break Some(format!("An error occured when checking Caddy HTTP server health: {:?}", e).into()),
+            Err(e) => break Some(format!("An error occured when checking Caddy HTTP server health: {:?}", e)),
         };
     };
 
-    if error.is_some() {
-        error!("{}", error.unwrap());
+    if let Some(error) = error {
+        error!("{}", error);
+        php_backend.stop();
 
         return ExitCode::from(1);
     }
@@ -241,34 +322,14 @@ fn serve_background(args: &ArgMatches) -> ExitCode {
     let rymfony_err_file = file_options.open(paths::get_rymfony_process_err_file()).unwrap();
 
     let mut cmd = Command::new(current_process_name::get().as_str());
-    cmd.stdout(Stdio::from(rymfony_log_file))
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::from(rymfony_log_file))
         .stderr(Stdio::from(rymfony_err_file))
         .arg("serve")
-        .arg("--port")
-        .arg(port.to_string());
-
-    if args.get_flag("no-tls") {
-        cmd.arg("--no-tls");
-    }
-    if args.get_flag("expose-server-header") {
-        cmd.arg("--expose-server-header");
-    }
-
-    let document_root = args.get_one::<String>("document-root").map(|s| s.as_str()).unwrap_or("").to_string();
-    if document_root != "" {
-        cmd.arg("--document-root").arg(document_root);
-    }
-
-    let passthru = args.get_one::<String>("passthru").map(|s| s.as_str()).unwrap_or("").to_string();
-    if passthru != "" {
-        cmd.arg("--passthru").arg(passthru);
-    }
-
-    let host = args.get_one::<String>("host").map(|s| s.as_str()).unwrap_or("").to_string();
-    if host != "" {
-        cmd.arg("--host").arg(host);
-    }
+        .args(serve_arguments(args, port));
 
+    // The subprocess is detached on purpose: it outlives this command, and "stop" kills it by PID.
+    #[allow(clippy::zombie_processes)]
     let subprocess = cmd.spawn().expect("Failed to start server as a background process");
 
     let pid = subprocess.id();
@@ -282,6 +343,44 @@ fn serve_background(args: &ArgMatches) -> ExitCode {
     ExitCode::from(0)
 }
 
+/// Arguments to pass to the "serve" command to start a server with the same options.
+pub(crate) fn serve_arguments(args: &ArgMatches, port: u16) -> Vec<String> {
+    let mut arguments = vec![String::from("--port"), port.to_string()];
+
+    if args.get_flag("no-tls") {
+        arguments.push(String::from("--no-tls"));
+    }
+    if args.get_flag("expose-server-header") {
+        arguments.push(String::from("--expose-server-header"));
+    }
+    if args.get_flag("watch") {
+        arguments.push(String::from("--watch"));
+    }
+
+    for option in ["document-root", "passthru", "host", "php-backend", "php-address"] {
+        let value = args.get_one::<String>(option).map(|s| s.as_str()).unwrap_or("");
+        if !value.is_empty() {
+            arguments.push(format!("--{}", option));
+            arguments.push(value.to_string());
+        }
+    }
+
+    // The background server may be restarted from another directory, like by "php:use".
+    if args.contains_id("project-dir") {
+        arguments.push(String::from("--project-dir"));
+        arguments.push(get_project_root().to_str().unwrap().to_string());
+    }
+
+    if let Some(mode) = args.get_one::<String>("xdebug") {
+        arguments.push(format!("--xdebug={}", mode));
+    }
+    if let Some(seconds) = args.get_one::<u32>("slow-threshold") {
+        arguments.push(format!("--slow-threshold={}", seconds));
+    }
+
+    arguments
+}
+
 fn get_document_root(document_root_arg: String) -> String {
     let path = PathBuf::from(&document_root_arg);
 
@@ -289,23 +388,26 @@ fn get_document_root(document_root_arg: String) -> String {
         return document_root_arg;
     }
 
-    let document_root =
-        if document_root_arg == "" { autodetect_document_root() } else { PathBuf::from(document_root_arg) };
+    let document_root = if document_root_arg.is_empty() {
+        autodetect_document_root()
+    } else {
+        PathBuf::from(document_root_arg)
+    };
 
     String::from(document_root.to_str().unwrap())
 }
 
 fn autodetect_document_root() -> PathBuf {
-    let current_dir = env::current_dir().unwrap();
+    let current_dir = get_project_root();
 
-    // {cwd}/public/ , usually recent projects
+    // {project}/public/ , usually recent projects
     let mut public_dir = PathBuf::from(&current_dir);
     public_dir.push("public/");
     if public_dir.is_dir() {
         return public_dir;
     }
 
-    // {cwd}/web/ , symfony 2 style
+    // {project}/web/ , symfony 2 style
     let mut web_dir = PathBuf::from(&current_dir);
     web_dir.push("web/");
     if web_dir.is_dir() {
@@ -0,0 +1,130 @@
+use crate::command_handling::CommandHandler;
+use crate::config::paths;
+use crate::php::binaries;
+use crate::php::php_server;
+use crate::php::xdebug;
+use crate::utils::stop_process;
+use clap::arg;
+use clap::ArgMatches;
+use clap::Command as ClapCommand;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Duration;
+
+const RESTART_TIMEOUT: Duration = Duration::from_secs(15);
+
+pub(crate) fn get_command() -> CommandHandler {
+    CommandHandler::new(
+        ClapCommand::new("server:xdebug")
+            .about("Enable or disable Xdebug in the running PHP server")
+            .after_help(
+                "
+Xdebug is only loaded in the PHP server started by Rymfony, never in your global PHP configuration.
+Only the PHP server is restarted, the HTTP server keeps running.
+
+To start a server with Xdebug enabled, use \"rymfony serve --xdebug\".
+
+Examples:
+
+$ rymfony server:xdebug on
+$ rymfony server:xdebug on --mode develop,debug
+$ rymfony server:xdebug off
+",
+            )
+            .arg(arg!(<STATE> "Whether Xdebug must be enabled").value_parser(["on", "off"]))
+            .arg(arg!(--mode <MODE> "The Xdebug mode").default_value(xdebug::DEFAULT_MODE)),
+        Box::new(execute),
+    )
+}
+
+pub(crate) fn execute(args: &ArgMatches) -> ExitCode {
+    if !paths::rymfony_pid_file().exists() {
+        error!("No server is running for this project. Run \"rymfony serve --xdebug\" to start one with Xdebug.");
+
+        return ExitCode::from(1);
+    }
+
+    if php_server::get_php_server_pids().is_empty() {
+        error!(
+            "The PHP server of this project is not managed by Rymfony, Xdebug must be configured in the server itself."
+        );
+
+        return ExitCode::from(1);
+    }
+
+    let mode = if args.get_one::<String>("STATE").unwrap() == "on" {
+        let mode = args.get_one::<String>("mode").unwrap();
+
+        if let Err(e) = check_xdebug(mode) {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        }
+
+        Some(mode.as_str())
+    } else {
+        None
+    };
+
+    if xdebug::get_mode().as_deref() == mode {
+        info!("Nothing to do, Xdebug is already {}.", describe(mode));
+
+        return ExitCode::from(0);
+    }
+
+    xdebug::set_mode(mode);
+
+    if !restart_php_server() {
+        error!("The PHP server did not restart in time. Run \"rymfony logs\" to check what happened.");
+
+        return ExitCode::from(1);
+    }
+
+    info!("PHP server restarted, Xdebug is {}.", describe(mode));
+
+    ExitCode::from(0)
+}
+
+fn check_xdebug(mode: &str) -> Result<(), Box<dyn std::error::Error>> {
+    xdebug::validate_mode(mode)?;
+
+    let binary = binaries::get_project_binary().ok_or("No PHP binary found for this project.")?;
+
+    let php_bin = binary.preferred_sapi().ok_or_else(|| format!("No PHP binary was found for PHP {}.", binary))?;
+
+    xdebug::get_server_arguments(&binary, &php_bin, mode)?;
+
+    Ok(())
+}
+
+fn describe(mode: Option<&str>) -> String {
+    match mode {
+        Some(mode) => format!("enabled with mode \"{}\"", mode),
+        None => String::from("disabled"),
+    }
+}
+
+/// Stops the PHP server processes, and waits for the running Rymfony server to start them again with the new configuration.
+fn restart_php_server() -> bool {
+    let previous_pids = php_server::get_php_server_pids();
+
+    for pid in previous_pids.iter() {
+        stop_process::stop(pid);
+    }
+
+    let step = Duration::from_millis(250);
+    let mut waited = Duration::ZERO;
+
+    while waited < RESTART_TIMEOUT {
+        thread::sleep(step);
+        waited += step;
+
+        // The PID file is written once every process has been restarted.
+        let pids = php_server::get_php_server_pids();
+        if !pids.is_empty() && pids.iter().all(|pid| !previous_pids.contains(pid)) {
+            return true;
+        }
+    }
+
+    false
+}
@@ -1,5 +1,6 @@
 use crate::command_handling::CommandHandler;
 use crate::config::paths;
+use crate::php::php_server;
 use crate::utils::project_directory::clean_rymfony_runtime_files;
 use crate::utils::stop_process;
 use clap::ArgMatches;
@@ -12,12 +13,16 @@ pub(crate) fn get_command() -> CommandHandler {
 }
 
 pub(crate) fn execute(_args: &ArgMatches) -> ExitCode {
+    stop_server();
+
+    ExitCode::from(0)
+}
+
+pub(crate) fn stop_server() {
     stop_rymfony();
     stop_php_server();
     stop_http_server();
     clean_rymfony_runtime_files();
-
-    ExitCode::from(0)
 }
 
 fn stop_rymfony() {
@@ -35,9 +40,10 @@ fn stop_rymfony() {
 fn stop_php_server() {
     let php_pid_file = paths::php_server_pid_file();
     if php_pid_file.exists() {
-        let pid = fs::read_to_string(&php_pid_file).unwrap();
-        stop_process::stop(pid.as_ref());
-        info!("Stopped PHP server running with PID {}", pid);
+        for pid in php_server::get_php_server_pids() {
+            stop_process::stop(pid.as_ref());
+            info!("Stopped PHP server running with PID {}", pid);
+        }
         let remove_result = fs::remove_file(&php_pid_file);
         if remove_result.is_err() {
             info!("Seems like PHP server was not running or was stopped when I checked for its status");
@@ -1,7 +1,5 @@
 use crate::php::structs::PhpBinary;
-use crate::php::structs::PhpVersion;
 use dirs::home_dir;
-use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::fs::read_to_string;
@@ -20,7 +18,7 @@ impl fmt::Display for ConfigError {
 
 impl Error for ConfigError {}
 
-pub(crate) fn save_binaries_to_config(binaries: &HashMap<PhpVersion, PhpBinary>) {
+pub(crate) fn save_binaries_to_config(binaries: &[PhpBinary]) {
     let serialized = serde_json::to_string_pretty(&binaries).unwrap();
 
     let versions_file_path = home_dir().unwrap().join(".rymfony").join("php-versions.json");
@@ -30,8 +28,7 @@ pub(crate) fn save_binaries_to_config(binaries: &HashMap<PhpVersion, PhpBinary>)
     versions_file.write_all(serialized.as_bytes()).expect("Could not write PHP versions to cache file.");
 }
 
-pub(crate) fn load_binaries_from_config(
-) -> std::result::Result<HashMap<PhpVersion, PhpBinary>, Box<dyn std::error::Error>> {
+pub(crate) fn load_binaries_from_config() -> std::result::Result<Vec<PhpBinary>, Box<dyn std::error::Error>> {
     let versions_file_path = home_dir().unwrap().join(".rymfony").join("php-versions.json");
 
     if !versions_file_path.exists() {
@@ -39,9 +36,8 @@ pub(crate) fn load_binaries_from_config(
     }
     trace!("File {} found", versions_file_path.to_str().unwrap());
 
-    let binaries: HashMap<PhpVersion, PhpBinary> =
-        serde_json::from_str(read_to_string(&versions_file_path).unwrap().as_str())
-            .expect("Unable to unserialize PHP binaries data");
+    let binaries: Vec<PhpBinary> = serde_json::from_str(read_to_string(&versions_file_path)?.as_str())
+        .map_err(|e| ConfigError(format!("Unable to unserialize PHP binaries data: {}", e)))?;
 
     Ok(binaries)
 }
@@ -51,7 +47,7 @@ pub(crate) fn clear_binaries_list() -> std::result::Result<(), Box<dyn std::erro
 
     if versions_file_path.exists() {
         remove_file(&versions_file_path)
-            .expect(format!("Unable to remove cache file {}", versions_file_path.to_str().unwrap()).as_str());
+            .unwrap_or_else(|_| panic!("Unable to remove cache file {}", versions_file_path.to_str().unwrap()));
     }
     Ok(())
 }
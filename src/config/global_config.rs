@@ -0,0 +1,109 @@
+use crate::config::project_config;
+use crate::config::project_config::CgiConfig;
+use crate::config::project_config::FpmConfig;
+use dirs::home_dir;
+use serde::Deserialize;
+use std::error::Error;
+use std::fs::read_to_string;
+use std::fs::write;
+use std::path::PathBuf;
+use toml_edit::Array;
+use toml_edit::DocumentMut;
+
+/// Global configuration file, in the "~/.rymfony" directory. The project configuration has precedence over it.
+pub(crate) const GLOBAL_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct GlobalConfig {
+    pub(crate) fpm: FpmConfig,
+    pub(crate) cgi: CgiConfig,
+    pub(crate) discovery: DiscoveryConfig,
+}
+
+/// PHP installations that discovery cannot find on its own, in the "[discovery]" table.
+/// They are managed with the "php:add" and "php:remove" commands.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct DiscoveryConfig {
+    /// Directories containing PHP binaries, or paths of PHP binaries.
+    pub(crate) paths: Vec<String>,
+}
+
+pub(crate) fn get_global_config_file() -> PathBuf {
+    home_dir().unwrap().join(".rymfony").join(GLOBAL_CONFIG_FILE)
+}
+
+/// Loads the global configuration, or the default one if there is no configuration file.
+pub(crate) fn load() -> GlobalConfig {
+    project_config::load_file(&get_global_config_file())
+}
+
+/// Replaces the paths of the "[discovery]" table, keeping the other settings of the global configuration.
+pub(crate) fn save_discovery_paths(paths: &[String]) -> Result<(), Box<dyn Error>> {
+    let config_file_path = get_global_config_file();
+
+    let content = if config_file_path.exists() { read_to_string(&config_file_path)? } else { String::new() };
+
+    write(&config_file_path, with_discovery_paths(&content, paths)?)?;
+
+    Ok(())
+}
+
+/// The file is edited in place, so that its comments and formatting are kept.
+/// An invalid file is not overwritten, so that the user can fix it.
+fn with_discovery_paths(content: &str, paths: &[String]) -> Result<String, Box<dyn Error>> {
+    let mut config: DocumentMut = content
+        .parse()
+        .map_err(|e| format!("Cannot update \"{}\": {}", get_global_config_file().to_str().unwrap(), e))?;
+
+    let discovery = config.entry("discovery").or_insert_with(toml_edit::table);
+    let discovery = discovery
+        .as_table_like_mut()
+        .ok_or("The \"discovery\" setting of the global configuration must be a table.")?;
+
+    let paths = toml_edit::value(paths.iter().collect::<Array>());
+    match discovery.get_mut("paths") {
+        // Replacing the value only keeps the comments above the key.
+        Some(item) => *item = paths,
+        None => {
+            discovery.insert("paths", paths);
+        },
+    }
+
+    Ok(config.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discovery_paths_are_saved_with_the_other_settings() {
+        let content = "[cgi]\nworkers = 4\n";
+        let paths = vec![String::from("/opt/php83/bin"), String::from("/srv/php/php-custom")];
+
+        let content = with_discovery_paths(content, &paths).unwrap();
+        let config: GlobalConfig = toml::from_str(&content).unwrap();
+
+        assert_eq!(config.cgi.workers, Some(4));
+        assert_eq!(config.discovery.paths, paths);
+
+        let content = with_discovery_paths(&content, &paths[1..]).unwrap();
+        let config: GlobalConfig = toml::from_str(&content).unwrap();
+
+        assert_eq!(config.discovery.paths, vec!["/srv/php/php-custom"]);
+        assert!(with_discovery_paths("[cgi", &paths).is_err());
+    }
+
+    #[test]
+    fn comments_are_kept_when_saving_discovery_paths() {
+        let content = "# PHP-CGI workers\n[cgi]\nworkers = 4 # one per CPU\n\n[discovery]\n# Custom builds\npaths = [\"/opt/php83/bin\"]\n";
+        let paths = vec![String::from("/opt/php83/bin"), String::from("/srv/php/php-custom")];
+
+        assert_eq!(
+            with_discovery_paths(content, &paths).unwrap(),
+            "# PHP-CGI workers\n[cgi]\nworkers = 4 # one per CPU\n\n[discovery]\n# Custom builds\npaths = [\"/opt/php83/bin\", \"/srv/php/php-custom\"]\n"
+        );
+    }
+}
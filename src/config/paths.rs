@@ -6,6 +6,11 @@ pub(crate) fn rymfony_pid_file() -> PathBuf {
     get_rymfony_project_directory().unwrap().join(".rymfony.pid")
 }
 
+/// Arguments of the running "serve" command, used to start it again with the same options.
+pub(crate) fn get_serve_arguments_file() -> PathBuf {
+    get_rymfony_project_directory().unwrap().join(".rymfony.args")
+}
+
 pub(crate) fn get_caddy_pid_file() -> PathBuf {
     get_rymfony_project_directory().unwrap().join(".caddy.pid")
 }
@@ -78,3 +83,29 @@ pub(crate) fn get_caddy_config_file() -> PathBuf {
 pub(crate) fn get_caddy_runtime_config_file() -> PathBuf {
     get_rymfony_project_directory().unwrap().join("Caddyfile.runtime")
 }
+
+/// Backtraces of the requests slower than the slow log timeout of PHP-FPM.
+pub(crate) fn get_php_slow_log_file() -> PathBuf {
+    get_rymfony_project_directory().unwrap().join("log").join("php-fpm.slow.log")
+}
+
+pub(crate) fn get_php_cgi_log_file() -> PathBuf {
+    get_rymfony_project_directory().unwrap().join("log").join("php-cgi.log")
+}
+
+/// Router script of PHP's built-in web server, passing requests to the project's entrypoint.
+pub(crate) fn get_php_router_file() -> PathBuf {
+    get_rymfony_project_directory().unwrap().join("router.php")
+}
+
+pub(crate) fn get_error_pages_dir() -> PathBuf {
+    get_rymfony_project_directory().unwrap().join("error_pages")
+}
+
+pub(crate) fn get_xdebug_mode_file() -> PathBuf {
+    get_rymfony_project_directory().unwrap().join(".xdebug")
+}
+
+pub(crate) fn get_php_shims_dir() -> PathBuf {
+    get_rymfony_project_directory().unwrap().join("bin")
+}
@@ -0,0 +1,136 @@
+use crate::utils::project_directory::get_project_root;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// Project configuration file, at the root of the project.
+pub(crate) const PROJECT_CONFIG_FILE: &str = ".rymfony.toml";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct ProjectConfig {
+    /// INI settings applied to the PHP server, like `memory_limit = "512M"`.
+    pub(crate) php_ini: BTreeMap<String, toml::Value>,
+    pub(crate) php: PhpConfig,
+    pub(crate) fpm: FpmConfig,
+    pub(crate) cgi: CgiConfig,
+    pub(crate) watch: WatchConfig,
+}
+
+/// Selects one of the PHP installations matching the project's version, in the "[php]" table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct PhpConfig {
+    /// Directory of the installation, or path of one of its binaries, like `path = "/opt/php-debug"`.
+    pub(crate) path: Option<String>,
+    /// Build flavor of the installation, like `flavor = "zts debug"`.
+    pub(crate) flavor: Option<String>,
+}
+
+/// PHP-FPM process manager settings, in the "[fpm]" table.
+/// Unset values fall back to the global configuration, and then to the defaults.
+#[cfg_attr(target_family = "windows", allow(dead_code))]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct FpmConfig {
+    pub(crate) pm: Option<FpmProcessManager>,
+    pub(crate) max_children: Option<u32>,
+    pub(crate) start_servers: Option<u32>,
+    pub(crate) min_spare_servers: Option<u32>,
+    pub(crate) max_spare_servers: Option<u32>,
+    pub(crate) max_requests: Option<u32>,
+    /// A number of seconds, or a value with a unit, like `"60s"` or `"5m"`.
+    pub(crate) request_terminate_timeout: Option<toml::Value>,
+    /// Requests running longer than this are logged with their backtrace, in the "slow" logs channel.
+    pub(crate) request_slowlog_timeout: Option<toml::Value>,
+}
+
+impl FpmConfig {
+    /// Completes this configuration with the values of another one.
+    pub(crate) fn or(self, other: FpmConfig) -> FpmConfig {
+        FpmConfig {
+            pm: self.pm.or(other.pm),
+            max_children: self.max_children.or(other.max_children),
+            start_servers: self.start_servers.or(other.start_servers),
+            min_spare_servers: self.min_spare_servers.or(other.min_spare_servers),
+            max_spare_servers: self.max_spare_servers.or(other.max_spare_servers),
+            max_requests: self.max_requests.or(other.max_requests),
+            request_terminate_timeout: self.request_terminate_timeout.or(other.request_terminate_timeout),
+            request_slowlog_timeout: self.request_slowlog_timeout.or(other.request_slowlog_timeout),
+        }
+    }
+}
+
+/// PHP-CGI settings, in the "[cgi]" table. Unset values fall back to the global configuration.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct CgiConfig {
+    /// Number of PHP-CGI processes, each of them handling one request at a time.
+    pub(crate) workers: Option<u32>,
+}
+
+/// Files watched by "serve --watch", in the "[watch]" table.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct WatchConfig {
+    /// Glob patterns relative to the project directory, replacing the default ones.
+    pub(crate) files: Option<Vec<String>>,
+}
+
+/// Files only read when PHP starts, or cached by OPcache until it restarts.
+const DEFAULT_WATCHED_FILES: [&str; 5] =
+    [".env*", "php.ini", "composer.lock", "config/preload.php", PROJECT_CONFIG_FILE];
+
+impl WatchConfig {
+    pub(crate) fn files(&self) -> Vec<String> {
+        match &self.files {
+            Some(files) => files.clone(),
+            None => DEFAULT_WATCHED_FILES.iter().map(|file| file.to_string()).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum FpmProcessManager {
+    Dynamic,
+    Static,
+    Ondemand,
+    /// Dynamic, with a number of children depending on the CPU count and the available memory.
+    Auto,
+}
+
+/// Loads the project configuration, or the default one if there is no configuration file.
+pub(crate) fn load() -> ProjectConfig {
+    load_file(&get_project_root().join(PROJECT_CONFIG_FILE))
+}
+
+/// Loads a TOML configuration file.
+/// An invalid file is reported and ignored, so that it never prevents the server from starting.
+pub(crate) fn load_file<T: DeserializeOwned + Default>(config_file_path: &Path) -> T {
+    if !config_file_path.exists() {
+        return T::default();
+    }
+
+    let file_name = config_file_path.to_str().unwrap();
+
+    let content = match read_to_string(config_file_path) {
+        Ok(content) => content,
+        Err(e) => {
+            warn!("Could not read \"{}\": {}", file_name, e);
+
+            return T::default();
+        },
+    };
+
+    match toml::from_str(&content) {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("Ignoring invalid \"{}\" file: {}", file_name, e);
+
+            T::default()
+        },
+    }
+}
@@ -0,0 +1,61 @@
+use crate::utils::date;
+use dirs::home_dir;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+use std::fs::write;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Registry of the projects having a Rymfony directory, in the "~/.rymfony" directory.
+pub(crate) const PROJECTS_FILE: &str = "projects.json";
+
+/// A project, registered under the hash naming its Rymfony directory.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub(crate) struct ProjectEntry {
+    pub(crate) path: String,
+    pub(crate) created_at: String,
+    pub(crate) last_used_at: String,
+}
+
+pub(crate) type ProjectRegistry = BTreeMap<String, ProjectEntry>;
+
+pub(crate) fn get_projects_file() -> PathBuf {
+    home_dir().unwrap().join(".rymfony").join(PROJECTS_FILE)
+}
+
+/// Loads the registry. An invalid file is reported and ignored, since it is created again as projects are used.
+pub(crate) fn load() -> ProjectRegistry {
+    let projects_file = get_projects_file();
+
+    let content = match read_to_string(&projects_file) {
+        Ok(content) => content,
+        Err(_) => return ProjectRegistry::new(),
+    };
+
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        warn!("Ignoring invalid \"{}\" file: {}", projects_file.to_str().unwrap(), e);
+
+        ProjectRegistry::new()
+    })
+}
+
+pub(crate) fn save(registry: &ProjectRegistry) -> io::Result<()> {
+    write(get_projects_file(), serde_json::to_string_pretty(registry).unwrap())
+}
+
+/// Adds the project to the registry, or updates its path and last use date.
+pub(crate) fn register(registry: &mut ProjectRegistry, hash: &str, project_path: &Path) {
+    let now = date::now();
+    let path = project_path.to_str().unwrap().to_string();
+
+    registry
+        .entry(hash.to_string())
+        .and_modify(|entry| {
+            entry.path = path.clone();
+            entry.last_used_at = now.clone();
+        })
+        .or_insert_with(|| ProjectEntry { path, created_at: now.clone(), last_used_at: now.clone() });
+}
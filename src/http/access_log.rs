@@ -0,0 +1,143 @@
+use crate::config::paths;
+use crate::utils::date::format_timestamp;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::read_to_string;
+
+/// Caddy redacts these headers in its access logs, so their real value is lost.
+pub(crate) const REDACTED_HEADER_VALUE: &str = "REDACTED";
+
+#[derive(Debug)]
+struct AccessLogError(String);
+
+impl fmt::Display for AccessLogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "An error occured: {}", self.0)
+    }
+}
+
+impl Error for AccessLogError {}
+
+/// One request handled by Caddy, as written to the vhost access log.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct CapturedRequest {
+    #[serde(skip)]
+    pub(crate) id: usize,
+    pub(crate) ts: f64,
+    #[serde(default)]
+    pub(crate) duration: f64,
+    #[serde(default)]
+    pub(crate) size: i64,
+    #[serde(default)]
+    pub(crate) status: u16,
+    #[serde(default)]
+    pub(crate) resp_headers: BTreeMap<String, Vec<String>>,
+    pub(crate) request: CapturedRequestDetails,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct CapturedRequestDetails {
+    pub(crate) method: String,
+    pub(crate) host: String,
+    pub(crate) uri: String,
+    #[serde(default)]
+    pub(crate) proto: String,
+    #[serde(default)]
+    pub(crate) headers: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    tls: Option<serde_json::Value>,
+}
+
+impl CapturedRequest {
+    pub(crate) fn scheme(&self) -> &str {
+        if self.request.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        }
+    }
+
+    pub(crate) fn url(&self) -> String {
+        format!("{}://{}{}", self.scheme(), self.request.host, self.request.uri)
+    }
+
+    /// Date of the request, in ISO 8601 format (UTC).
+    pub(crate) fn date(&self) -> String {
+        format_timestamp(self.ts)
+    }
+}
+
+/// Returns all requests captured in the current project's HTTP access log.
+/// IDs start at 1 and follow the order of the log file.
+pub(crate) fn get_captured_requests() -> Result<Vec<CapturedRequest>, Box<dyn Error>> {
+    let log_file = paths::get_http_vhost_log_file();
+
+    if !log_file.exists() {
+        return Err(Box::new(AccessLogError(format!(
+            "No HTTP access log found at \"{}\". Run \"rymfony serve\" and send some requests first.",
+            log_file.to_str().unwrap()
+        ))));
+    }
+
+    Ok(parse_access_log(&read_to_string(&log_file)?))
+}
+
+pub(crate) fn get_captured_request(id: usize) -> Result<CapturedRequest, Box<dyn Error>> {
+    get_captured_requests()?
+        .into_iter()
+        .find(|request| request.id == id)
+        .ok_or_else(|| Box::new(AccessLogError(format!("No captured request with ID {}", id))) as Box<dyn Error>)
+}
+
+fn parse_access_log(content: &str) -> Vec<CapturedRequest> {
+    let mut requests = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        // The access log may also contain non-request entries (like debug messages), they are skipped.
+        match serde_json::from_str::<CapturedRequest>(line) {
+            Ok(mut request) => {
+                request.id = requests.len() + 1;
+                requests.push(request);
+            },
+            Err(e) => trace!("Skipping access log line: {}", e),
+        }
+    }
+
+    requests
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOG: &str = r#"
+{"level":"info","ts":1672531200.5,"logger":"http.log.access.log0","msg":"handled request","request":{"remote_ip":"127.0.0.1","remote_port":"41342","proto":"HTTP/2.0","method":"GET","host":"127.0.0.1:8000","uri":"/?page=2","headers":{"Accept":["*/*"],"Cookie":["REDACTED"]},"tls":{"resumed":false,"version":772}},"user_id":"","duration":0.0125,"size":10900,"status":200,"resp_headers":{"Content-Type":["text/html; charset=UTF-8"]}}
+{"level":"debug","ts":1672531201.0,"logger":"http.handlers.reverse_proxy","msg":"upstream roundtrip"}
+{"level":"info","ts":1672531202.25,"logger":"http.log.access.log0","msg":"handled request","request":{"remote_ip":"127.0.0.1","remote_port":"41343","proto":"HTTP/1.1","method":"POST","host":"localhost:8000","uri":"/login","headers":{}},"duration":0.5,"size":0,"status":302,"resp_headers":{"Location":["/"]}}
+"#;
+
+    #[test]
+    fn parse_skips_non_request_lines() {
+        let requests = parse_access_log(LOG);
+
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].id, 1);
+        assert_eq!(requests[1].id, 2);
+        assert_eq!(requests[1].request.method, "POST");
+    }
+
+    #[test]
+    fn url_depends_on_tls() {
+        let requests = parse_access_log(LOG);
+
+        assert_eq!(requests[0].url(), "https://127.0.0.1:8000/?page=2");
+        assert_eq!(requests[1].url(), "http://localhost:8000/login");
+    }
+}
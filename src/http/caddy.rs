@@ -4,6 +4,7 @@ use regex::Regex;
 use std::fs;
 #[cfg(not(target_os = "windows"))]
 use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::Stdio;
@@ -11,15 +12,15 @@ use std::process::Stdio;
 #[cfg(not(target_os = "windows"))]
 use runas::Command as SudoCommand;
 
-const CADDY_VERSION_REGEX: &'static str = r"^v(2\.\d+\.\d+) ";
+const CADDY_VERSION_REGEX: &str = r"^v(2\.\d+\.\d+) ";
 
 #[cfg(target_os = "windows")]
 const CADDY_BIN_FILE: &'static str = "caddy.exe";
 
 #[cfg(not(target_os = "windows"))]
-const CADDY_BIN_FILE: &'static str = "caddy";
+const CADDY_BIN_FILE: &str = "caddy";
 
-pub(crate) const CADDYFILE: &'static str = "
+pub(crate) const CADDYFILE: &str = "
 # ⚠⚠⚠
 # This file is a *template* created by Rymfony.
 # The variables you see in brackets \"{{ … }}\" are
@@ -55,13 +56,30 @@ pub(crate) const CADDYFILE: &'static str = "
         {{ debug }}level DEBUG
     }
 
-    php_fastcgi 127.0.0.1:{{ php_port }} {
-        env SERVER_SOFTWARE \"Rymfony/Caddy\"
-        index {{ php_entrypoint_file }}
-        resolve_root_symlink
-    }
+    {{ fastcgi }}php_fastcgi {{ php_upstreams }} {
+    {{ fastcgi }}    env SERVER_SOFTWARE \"Rymfony/Caddy\"
+    {{ fastcgi }}    # Each PHP-CGI worker handles one request at a time.
+    {{ fastcgi }}    lb_policy least_conn
+    {{ fastcgi }}    lb_try_duration 5s
+    {{ fastcgi }}    index {{ php_entrypoint_file }}
+    {{ fastcgi }}    resolve_root_symlink
+    {{ fastcgi }}}
+
+    # PHP's built-in web server speaks HTTP, and serves static files by itself.
+    {{ builtin_server }}reverse_proxy {{ php_upstreams }}
 
     file_server
+
+    # When PHP is down, Rymfony serves a diagnostic page instead of an empty \"502 Bad Gateway\".
+    handle_errors {
+        @php_backend_down expression `{err.status_code} >= 502 && {err.status_code} <= 504`
+        handle @php_backend_down {
+            root * {{ error_pages_dir }}
+            rewrite * /{{ php_backend_down_page }}
+            header Cache-Control no-store
+            file_server
+        }
+    }
 }
 ";
 
@@ -103,7 +121,7 @@ pub(crate) fn get_caddy_path() -> PathBuf {
 }
 
 fn caddy_from_path_env() -> which::Result<PathBuf> {
-    return which::which("caddy");
+    which::which("caddy")
 }
 
 fn check_caddy_version(caddy_path: &PathBuf) {
@@ -115,7 +133,7 @@ fn check_caddy_version(caddy_path: &PathBuf) {
         .stderr(Stdio::null())
         .arg("version")
         .output()
-        .expect(&format!("Could not execute Caddy at path \"{}\"", caddy_path.to_str().unwrap()));
+        .unwrap_or_else(|_| panic!("Could not execute Caddy at path \"{}\"", caddy_path.to_str().unwrap()));
 
     let stdout = String::from_utf8(output.stdout).expect("Could not convert Caddy's output to a string.");
 
@@ -127,7 +145,7 @@ fn check_caddy_version(caddy_path: &PathBuf) {
 }
 
 #[cfg(target_os = "linux")]
-fn set_http_capabilities(caddy_path: &PathBuf) {
+fn set_http_capabilities(caddy_path: &Path) {
     // TODO:
     // Checking capabilities might be done with this kind of command:
     // echo "{\ndebug\n}\nhttp://127.0.0.1 {\n root * `pwd`\n file_server\n}\n" | ~/.rymfony/uuid/caddy_uncap run --config=- --adapter=caddyfile
@@ -145,7 +163,7 @@ fn set_http_capabilities(caddy_path: &PathBuf) {
 
         let status = SudoCommand::new("setcap")
             .arg("cap_net_bind_service=+ep")
-            .arg(&caddy_path.to_str().unwrap())
+            .arg(caddy_path.to_str().unwrap())
             .status()
             .expect(
                 "The \"setcap\" command did not execute when trying to give Caddy the ability to listen to port 80.",
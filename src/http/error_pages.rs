@@ -0,0 +1,158 @@
+use crate::config::paths;
+use crate::php::backend::BackendAddress;
+use crate::php::backend::PhpBackend;
+use crate::utils::date;
+use std::fs::create_dir_all;
+use std::fs::write;
+use std::fs::File;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
+
+pub(crate) const PHP_BACKEND_DOWN_PAGE_FILE: &str = "php_backend_down.html";
+
+const LOG_LINES_TO_DISPLAY: usize = 30;
+
+// Only the end of log files is read, so that huge logs don't slow down the page generation.
+const LOG_BYTES_TO_READ: u64 = 64 * 1024;
+
+const BACKEND_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+// The placeholders between brackets {{ }} will be replaced with proper values.
+const PHP_BACKEND_DOWN_PAGE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta http-equiv="refresh" content="2">
+    <title>PHP server is not responding - Rymfony</title>
+    <style>
+        body { font-family: sans-serif; margin: 2em auto; max-width: 1100px; color: #222; }
+        h1 { color: #b22222; }
+        pre { background: #f4f4f4; border: 1px solid #ddd; padding: 1em; overflow: auto; font-size: 0.85em; }
+        .refresh { color: #666; }
+    </style>
+</head>
+<body>
+    <h1>The PHP server is not responding</h1>
+    <p>
+        Rymfony could not reach the <strong>{{ backend }}</strong> server listening to {{ addresses }}.
+        It will be restarted automatically, or you can restart Rymfony yourself.
+    </p>
+    <p class="refresh">This page refreshes every 2 seconds, and your application will show up as soon as the PHP server is back.</p>
+    <p class="refresh">Last update: {{ date }}</p>
+{{ logs }}
+</body>
+</html>
+"#;
+
+/// Writes the diagnostic page served by Caddy when PHP is down,
+/// and keeps it up-to-date in the background while one of the PHP backend addresses is not reachable.
+pub(crate) fn watch_php_backend(backend: &dyn PhpBackend) {
+    let name = backend.name();
+    let addresses = backend.addresses();
+    let log_files = backend.log_files();
+
+    write_php_backend_down_page(&name, &addresses, &log_files);
+
+    thread::spawn(move || loop {
+        thread::sleep(BACKEND_CHECK_INTERVAL);
+
+        if let Some(address) = addresses.iter().find(|address| !address.is_reachable()) {
+            trace!("PHP server is not reachable at {}, updating error page.", address);
+            write_php_backend_down_page(&name, &addresses, &log_files);
+        }
+    });
+}
+
+fn write_php_backend_down_page(name: &str, addresses: &[BackendAddress], log_files: &[PathBuf]) {
+    let error_pages_dir = paths::get_error_pages_dir();
+
+    if !error_pages_dir.is_dir() {
+        create_dir_all(&error_pages_dir).expect("Could not create error pages directory for project.");
+    }
+
+    let page = render_php_backend_down_page(name, addresses, log_files, &date::now());
+
+    if let Err(e) = write(error_pages_dir.join(PHP_BACKEND_DOWN_PAGE_FILE), page) {
+        warn!("Could not write PHP error page: {}", e);
+    }
+}
+
+fn render_php_backend_down_page(name: &str, addresses: &[BackendAddress], log_files: &[PathBuf], date: &str) -> String {
+    let logs: String = log_files
+        .iter()
+        .map(|log_file| {
+            let file_name = log_file.file_name().unwrap().to_str().unwrap();
+            let lines = tail_lines(log_file, LOG_LINES_TO_DISPLAY);
+            let content = if lines.is_empty() { String::from("(empty)") } else { lines.join("\n") };
+
+            format!("    <h2>Last lines of <code>{}</code></h2>\n    <pre>{}</pre>\n", file_name, escape_html(&content))
+        })
+        .collect();
+
+    let addresses: Vec<String> =
+        addresses.iter().map(|address| format!("<code>{}</code>", escape_html(&address.to_string()))).collect();
+
+    PHP_BACKEND_DOWN_PAGE
+        .replace("{{ backend }}", &escape_html(name))
+        .replace("{{ addresses }}", &addresses.join(", "))
+        .replace("{{ date }}", &escape_html(date))
+        .replace("{{ logs }}", &logs)
+}
+
+fn tail_lines(path: &Path, lines_count: usize) -> Vec<String> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(_) => return Vec::new(),
+    };
+
+    let length = file.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+    if length > LOG_BYTES_TO_READ && file.seek(SeekFrom::Start(length - LOG_BYTES_TO_READ)).is_err() {
+        return Vec::new();
+    }
+
+    let mut bytes = Vec::new();
+    if file.read_to_end(&mut bytes).is_err() {
+        return Vec::new();
+    }
+
+    let content = String::from_utf8_lossy(&bytes);
+    let lines: Vec<&str> = content.lines().filter(|line| !line.trim().is_empty()).collect();
+
+    lines[lines.len().saturating_sub(lines_count)..].iter().map(|line| line.to_string()).collect()
+}
+
+fn escape_html(content: &str) -> String {
+    content.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_contains_backend_and_escaped_logs() {
+        let log_file = std::env::temp_dir().join("rymfony_error_page_test.log");
+        let lines: Vec<String> = (1..=40).map(|i| format!("line {}", i)).collect();
+        write(&log_file, format!("{}\n<b>Fatal</b> error\n", lines.join("\n"))).unwrap();
+
+        let page = render_php_backend_down_page(
+            "PHP-FPM",
+            &[BackendAddress::local(60000)],
+            std::slice::from_ref(&log_file),
+            "now",
+        );
+
+        std::fs::remove_file(&log_file).unwrap();
+
+        assert!(page.contains("<strong>PHP-FPM</strong>"));
+        assert!(page.contains("<code>127.0.0.1:60000</code>"));
+        assert!(page.contains("&lt;b&gt;Fatal&lt;/b&gt; error"));
+        assert!(page.contains("line 40"));
+        assert!(!page.contains("line 11\n"));
+    }
+}
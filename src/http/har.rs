@@ -0,0 +1,166 @@
+use crate::http::access_log::CapturedRequest;
+use crate::APPLICATION_NAME;
+use crate::APP_VERSION_METADATA;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+// Only the parts of the HAR 1.2 format that can be filled from Caddy's access log are implemented.
+// @see http://www.softwareishard.com/blog/har-12-spec/
+
+#[derive(Serialize)]
+pub(crate) struct Har {
+    log: HarLog,
+}
+
+#[derive(Serialize)]
+struct HarLog {
+    version: &'static str,
+    creator: HarCreator,
+    entries: Vec<HarEntry>,
+}
+
+#[derive(Serialize)]
+struct HarCreator {
+    name: &'static str,
+    version: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarEntry {
+    started_date_time: String,
+    time: f64,
+    request: HarRequest,
+    response: HarResponse,
+    cache: BTreeMap<String, String>,
+    timings: HarTimings,
+    comment: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarRequest {
+    method: String,
+    url: String,
+    http_version: String,
+    cookies: Vec<HarNameValue>,
+    headers: Vec<HarNameValue>,
+    query_string: Vec<HarNameValue>,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarResponse {
+    status: u16,
+    status_text: String,
+    http_version: String,
+    cookies: Vec<HarNameValue>,
+    headers: Vec<HarNameValue>,
+    content: HarContent,
+    #[serde(rename = "redirectURL")]
+    redirect_url: String,
+    headers_size: i64,
+    body_size: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HarContent {
+    size: i64,
+    mime_type: String,
+}
+
+#[derive(Serialize)]
+struct HarNameValue {
+    name: String,
+    value: String,
+}
+
+#[derive(Serialize)]
+struct HarTimings {
+    send: f64,
+    wait: f64,
+    receive: f64,
+}
+
+impl Har {
+    pub(crate) fn from_requests(requests: &[CapturedRequest]) -> Har {
+        Har {
+            log: HarLog {
+                version: "1.2",
+                creator: HarCreator { name: APPLICATION_NAME, version: APP_VERSION_METADATA.trim().to_string() },
+                entries: requests.iter().map(HarEntry::from_request).collect(),
+            },
+        }
+    }
+}
+
+impl HarEntry {
+    fn from_request(captured: &CapturedRequest) -> HarEntry {
+        let duration_ms = captured.duration * 1000.0;
+        let first_header = |name: &str| {
+            captured
+                .resp_headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .and_then(|(_, values)| values.first().cloned())
+                .unwrap_or_default()
+        };
+
+        HarEntry {
+            started_date_time: captured.date(),
+            time: duration_ms,
+            request: HarRequest {
+                method: captured.request.method.clone(),
+                url: captured.url(),
+                http_version: captured.request.proto.clone(),
+                cookies: Vec::new(),
+                headers: headers_to_har(&captured.request.headers),
+                query_string: query_string_to_har(&captured.request.uri),
+                headers_size: -1,
+                body_size: -1,
+            },
+            response: HarResponse {
+                status: captured.status,
+                status_text: String::new(),
+                http_version: captured.request.proto.clone(),
+                cookies: Vec::new(),
+                headers: headers_to_har(&captured.resp_headers),
+                content: HarContent { size: captured.size, mime_type: first_header("Content-Type") },
+                redirect_url: first_header("Location"),
+                headers_size: -1,
+                body_size: captured.size,
+            },
+            cache: BTreeMap::new(),
+            timings: HarTimings { send: 0.0, wait: duration_ms, receive: 0.0 },
+            comment: format!("Rymfony request #{}", captured.id),
+        }
+    }
+}
+
+fn headers_to_har(headers: &BTreeMap<String, Vec<String>>) -> Vec<HarNameValue> {
+    headers
+        .iter()
+        .flat_map(|(name, values)| {
+            values.iter().map(move |value| HarNameValue { name: name.clone(), value: value.clone() })
+        })
+        .collect()
+}
+
+fn query_string_to_har(uri: &str) -> Vec<HarNameValue> {
+    let query = match uri.split_once('?') {
+        Some((_, query)) => query,
+        None => return Vec::new(),
+    };
+
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            HarNameValue { name: name.to_string(), value: value.to_string() }
+        })
+        .collect()
+}
@@ -1,6 +1,9 @@
 use crate::config::paths;
 use crate::http::caddy::get_caddy_path;
 use crate::http::caddy::CADDYFILE;
+use crate::http::error_pages::PHP_BACKEND_DOWN_PAGE_FILE;
+use crate::php::backend::BackendProtocol;
+use crate::php::backend::PhpBackend;
 use std::fs::read_to_string;
 use std::fs::write;
 use std::fs::File;
@@ -22,11 +25,12 @@ pub(crate) struct CaddyCommandInput {
 /// * The full Caddy command to be executed
 /// * The Caddy configuration as a string (equivalent to a Caddyfile), to be sent to Caddy's STDIN fd
 ///
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn get_caddy_start_command(
     use_tls: bool,
     host_name: String,
     http_port: u16,
-    php_port: u16,
+    php_backend: &dyn PhpBackend,
     document_root: String,
     php_entrypoint_file: String,
     add_server_sign: bool,
@@ -68,17 +72,35 @@ pub(crate) fn get_caddy_start_command(
             read_to_string(&caddy_config_file).expect("Could not read base Caddyfile config file.")
         };
 
+        let php_upstreams: Vec<String> =
+            php_backend.addresses().iter().map(|address| address.caddy_upstream()).collect();
+
+        // Templates created by older versions have a single local PHP upstream.
+        config = config.replace("127.0.0.1:{{ php_port }}", "{{ php_upstreams }}");
+
+        let use_builtin_server = php_backend.protocol() == BackendProtocol::Http;
+        if use_builtin_server && !config.contains("{{ builtin_server }}") {
+            warn!(
+                "The \"{}\" template does not support PHP's built-in web server. Remove it so that Rymfony can create a new one.",
+                caddy_config_file.to_str().unwrap()
+            );
+        }
+
         config = config
+            .replace("{{ builtin_server }}", if use_builtin_server { "" } else { "#" })
             .replace("{{ debug }}", if debug { "" } else { "#" })
             .replace("{{ document_root }}", document_root.as_str())
+            .replace("{{ error_pages_dir }}", paths::get_error_pages_dir().to_str().unwrap())
+            .replace("{{ fastcgi }}", if use_builtin_server { "#" } else { "" })
             .replace("{{ host }}", &host_name)
             .replace("{{ server_port }}", &http_port.to_string())
             .replace("{{ https_port }}", &http_port.to_string())
             .replace("{{ show_http_port }}", if use_tls { "#" } else { "" })
             .replace("{{ log_file }}", paths::get_http_server_log_file().to_str().unwrap())
             .replace("{{ log_level }}", if debug { "DEBUG" } else { "INFO" })
+            .replace("{{ php_backend_down_page }}", PHP_BACKEND_DOWN_PAGE_FILE)
             .replace("{{ php_entrypoint_file }}", php_entrypoint_file.as_str())
-            .replace("{{ php_port }}", &php_port.to_string())
+            .replace("{{ php_upstreams }}", &php_upstreams.join(" "))
             .replace("{{ protocol }}", if use_tls { "" } else { "http://" })
             .replace("{{ use_tls }}", if use_tls { "" } else { "#" })
             .replace("{{ vhost_log_file }}", paths::get_http_vhost_log_file().to_str().unwrap())
@@ -138,12 +160,12 @@ pub(crate) fn start_caddy(caddy_command: &mut Command, caddy_config: String) ->
                 }
             }
 
-            if stderr == "".to_string() {
+            if stderr.is_empty() {
                 let mut bytes = Vec::new();
                 let mut stderr_file = open_stderr_file(&std_file_options);
                 stderr_file.seek(SeekFrom::Start(file_pointer)).unwrap();
                 stderr_file.read_to_end(&mut bytes).unwrap();
-                let bytes_string = String::from_utf8(bytes.into()).unwrap();
+                let bytes_string = String::from_utf8(bytes).unwrap();
 
                 error!("Caddy failed to start with exit code {} and following error:", exit_code);
                 let error_lines = bytes_string.trim().split("\n");
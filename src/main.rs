@@ -12,35 +12,74 @@ mod command_handling;
 mod logging;
 
 mod config {
+    #[allow(clippy::module_inception)]
     pub(crate) mod config;
+    pub(crate) mod global_config;
     pub(crate) mod paths;
+    pub(crate) mod project_config;
+    pub(crate) mod projects;
 }
 
 mod commands {
+    pub(crate) mod composer;
+    pub(crate) mod console;
     pub(crate) mod logs;
     pub(crate) mod new_symfony;
+    pub(crate) mod php;
+    pub(crate) mod php_add;
+    pub(crate) mod php_info;
+    pub(crate) mod php_ini;
     pub(crate) mod php_list;
+    pub(crate) mod php_remove;
+    pub(crate) mod php_use;
+    pub(crate) mod project_move;
+    pub(crate) mod requests_export;
+    pub(crate) mod requests_list;
+    pub(crate) mod requests_replay;
     pub(crate) mod serve;
+    pub(crate) mod server_xdebug;
     pub(crate) mod stop;
 }
 
 mod utils {
     pub(crate) mod current_process_name;
+    pub(crate) mod date;
+    pub(crate) mod file_watcher;
     pub(crate) mod network;
     pub(crate) mod project_directory;
+    pub(crate) mod prompt;
     pub(crate) mod stop_process;
+    pub(crate) mod table;
 }
 
 mod php {
+    pub(crate) mod backend;
     pub(crate) mod binaries;
+    pub(crate) mod build_info;
+    pub(crate) mod cli;
+    pub(crate) mod discovery;
+    pub(crate) mod environment;
+    pub(crate) mod error_log;
+    #[cfg(not(target_family = "windows"))]
+    pub(crate) mod fpm_pool;
+    pub(crate) mod ini;
     pub(crate) mod php_server;
+    pub(crate) mod server_builtin;
     pub(crate) mod server_cgi;
+    pub(crate) mod server_external;
     pub(crate) mod server_fpm;
+    #[cfg(not(target_family = "windows"))]
+    pub(crate) mod slow_log;
     pub(crate) mod structs;
+    pub(crate) mod version_constraint;
+    pub(crate) mod xdebug;
 }
 
 mod http {
+    pub(crate) mod access_log;
     pub(crate) mod caddy;
+    pub(crate) mod error_pages;
+    pub(crate) mod har;
     pub(crate) mod proxy_server;
 }
 
@@ -51,17 +90,31 @@ use clap::ColorChoice;
 use clap::Command as ClapCommand;
 use dirs::home_dir;
 use std::fs;
+use std::path::Path;
 use std::process::Command;
 use std::process::ExitCode;
 
 fn application_commands() -> CommandList {
     CommandList {
         commands: vec![
-            Box::new(commands::logs::get_command()),
-            Box::new(commands::php_list::get_command()),
-            Box::new(commands::serve::get_command()),
-            Box::new(commands::stop::get_command()),
-            Box::new(commands::new_symfony::get_command()),
+            commands::composer::get_command(),
+            commands::console::get_command(),
+            commands::logs::get_command(),
+            commands::php::get_command(),
+            commands::php_add::get_command(),
+            commands::php_info::get_command(),
+            commands::php_ini::get_command(),
+            commands::php_list::get_command(),
+            commands::php_remove::get_command(),
+            commands::php_use::get_command(),
+            commands::project_move::get_command(),
+            commands::requests_export::get_command(),
+            commands::requests_list::get_command(),
+            commands::requests_replay::get_command(),
+            commands::serve::get_command(),
+            commands::server_xdebug::get_command(),
+            commands::stop::get_command(),
+            commands::new_symfony::get_command(),
         ],
     }
 }
@@ -78,13 +131,22 @@ fn main() -> ExitCode {
 
     let application = get_application().subcommands(subcommands);
 
-    let arg_matches = application.get_matches();
+    let args = application_commands.prepare_args(&application, std::env::args_os().collect());
+    let arg_matches = application.get_matches_from(args);
 
     let verbosity_level: &u8 = arg_matches.get_one::<u8>("verbose").unwrap_or(&0);
     let is_quiet = arg_matches.get_flag("quiet");
 
     logging::set_verbosity_value(*verbosity_level, is_quiet);
 
+    if let Some(project_dir) = arg_matches.get_one::<String>("project-dir") {
+        if let Err(e) = utils::project_directory::set_project_root(Path::new(project_dir)) {
+            error!("{}", e);
+
+            return ExitCode::from(1);
+        }
+    }
+
     let subcommand_name = arg_matches.subcommand_name();
     let args = if let Some(subcommand_name) = subcommand_name {
         arg_matches.subcommand_matches(subcommand_name)
@@ -131,6 +193,13 @@ https://github.com/Orbitale/Rymfony
                 .action(ArgAction::Count)
                 .help("Set the verbosity level. -v for debug, -vv for trace, -vvv to trace executed modules"),
         )
+        .arg(
+            Arg::new("project-dir")
+                .long("project-dir")
+                .global(true)
+                .value_name("DIRECTORY_PATH")
+                .help("The root directory of the project. By default, the nearest parent directory with a \"composer.json\", \".php-version\" or \".rymfony.toml\" file"),
+        )
         .arg(
             Arg::new("quiet")
                 .short('q')
@@ -162,9 +231,40 @@ fn default_command() -> Option<ExitCode> {
 #[cfg(test)]
 mod main_tests {
     use super::*;
+    use std::ffi::OsString;
 
     #[test]
     fn verify_cli() {
         get_application().debug_assert();
     }
+
+    #[test]
+    fn passthrough_arguments_follow_global_options() {
+        let application_commands = application_commands();
+        let application = get_application().subcommands(application_commands.subcommands());
+        let prepare_args = |args: &[&str]| -> Vec<String> {
+            application_commands
+                .prepare_args(&application, args.iter().map(OsString::from).collect())
+                .into_iter()
+                .map(|arg| arg.into_string().unwrap())
+                .collect()
+        };
+
+        assert_eq!(
+            prepare_args(&["rymfony", "--project-dir", "/tmp/ptest", "php", "-v"]),
+            ["rymfony", "--project-dir", "/tmp/ptest", "php", "--", "-v"]
+        );
+        assert_eq!(
+            prepare_args(&["rymfony", "--project-dir", "/tmp/ptest", "console", "--help"]),
+            ["rymfony", "--project-dir", "/tmp/ptest", "console", "--", "--help"]
+        );
+        assert_eq!(
+            prepare_args(&["rymfony", "-vv", "--project-dir=/tmp/ptest", "php", "-v"]),
+            ["rymfony", "-vv", "--project-dir=/tmp/ptest", "php", "--", "-v"]
+        );
+        assert_eq!(
+            prepare_args(&["rymfony", "--project-dir", "/tmp/ptest", "serve", "-d"]),
+            ["rymfony", "--project-dir", "/tmp/ptest", "serve", "-d"]
+        );
+    }
 }
@@ -0,0 +1,171 @@
+use crate::php::php_server;
+use crate::php::php_server::ManagedBackend;
+use crate::php::server_external::ExternalBackend;
+use crate::php::structs::PhpServerSapi;
+use crate::php::xdebug;
+use crate::utils::network::is_address_open;
+use std::error::Error;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Backends accepted by the "--php-backend" option of the "serve" command.
+pub(crate) const BACKEND_NAMES: [&str; 5] = ["auto", "fpm", "cgi", "builtin", "external"];
+
+#[derive(Debug)]
+pub(crate) struct BackendError(pub(crate) String);
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "An error occured: {}", self.0)
+    }
+}
+
+impl Error for BackendError {}
+
+/// How Caddy sends requests to the PHP backend.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum BackendProtocol {
+    FastCgi,
+    Http,
+}
+
+/// Address a PHP backend listens to.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum BackendAddress {
+    Tcp { host: String, port: u16 },
+    Socket(PathBuf),
+}
+
+impl BackendAddress {
+    pub(crate) fn local(port: u16) -> BackendAddress {
+        BackendAddress::Tcp { host: String::from("127.0.0.1"), port }
+    }
+
+    /// Parses "host:port", a port alone, or a Unix socket path, optionally prefixed with "unix:".
+    pub(crate) fn parse(address: &str) -> Result<BackendAddress, BackendError> {
+        let address = address.trim();
+
+        if let Some(path) = address.strip_prefix("unix:") {
+            return Ok(BackendAddress::Socket(PathBuf::from(path)));
+        }
+        if address.contains('/') || address.contains('\\') || address.ends_with(".sock") {
+            return Ok(BackendAddress::Socket(PathBuf::from(address)));
+        }
+
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) => (host.trim_start_matches('[').trim_end_matches(']'), port),
+            None => ("", address),
+        };
+        let port = port
+            .parse::<u16>()
+            .map_err(|_| BackendError(format!("\"{}\" is not a valid PHP backend address.", address)))?;
+        let host = if host.is_empty() { "127.0.0.1" } else { host };
+
+        Ok(BackendAddress::Tcp { host: host.to_string(), port })
+    }
+
+    /// Upstream address in Caddy's format.
+    pub(crate) fn caddy_upstream(&self) -> String {
+        match self {
+            BackendAddress::Tcp { host, port } if host.contains(':') => format!("[{}]:{}", host, port),
+            BackendAddress::Tcp { host, port } => format!("{}:{}", host, port),
+            BackendAddress::Socket(path) => format!("unix/{}", path.to_str().unwrap()),
+        }
+    }
+
+    pub(crate) fn is_reachable(&self) -> bool {
+        match self {
+            BackendAddress::Tcp { host, port } => is_address_open(host, *port),
+            #[cfg(not(target_family = "windows"))]
+            BackendAddress::Socket(path) => std::os::unix::net::UnixStream::connect(path).is_ok(),
+            #[cfg(target_family = "windows")]
+            BackendAddress::Socket(path) => path.exists(),
+        }
+    }
+}
+
+impl fmt::Display for BackendAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackendAddress::Tcp { .. } => write!(f, "{}", self.caddy_upstream()),
+            BackendAddress::Socket(path) => write!(f, "unix:{}", path.to_str().unwrap()),
+        }
+    }
+}
+
+/// A PHP server that Caddy sends the PHP requests to.
+pub(crate) trait PhpBackend {
+    /// Name displayed in logs and on the error page.
+    fn name(&self) -> String;
+
+    fn protocol(&self) -> BackendProtocol;
+
+    fn addresses(&self) -> Vec<BackendAddress>;
+
+    /// Log files displayed on the error page when the backend is not reachable.
+    fn log_files(&self) -> Vec<PathBuf>;
+
+    fn start(&mut self) -> Result<(), Box<dyn Error>>;
+
+    /// Called periodically by the "serve" command, to restart what needs to be.
+    fn check_health(&mut self) -> Result<(), Box<dyn Error>>;
+
+    /// Applies changes to the PHP config and to the files PHP only reads once, like with "serve --watch".
+    fn reload(&mut self) -> Result<(), Box<dyn Error>>;
+
+    fn stop(&mut self);
+}
+
+/// Creates the backend selected with the "--php-backend" option.
+/// An address alone selects the external backend.
+pub(crate) fn create(
+    name: &str,
+    address: Option<&str>,
+    document_root: &str,
+    php_entrypoint_file: &str,
+) -> Result<Box<dyn PhpBackend>, Box<dyn Error>> {
+    let sapi = match (name, address) {
+        ("external", None) => {
+            return Err(Box::new(BackendError(String::from(
+                "The external PHP backend needs an address, like \"--php-address=127.0.0.1:9000\".",
+            ))))
+        },
+        ("external", Some(_)) | ("auto", Some(_)) if xdebug::get_mode().is_some() => {
+            return Err(Box::new(BackendError(String::from(
+                "Xdebug cannot be enabled by Rymfony in an external PHP server, it must be configured in the server itself.",
+            ))))
+        },
+        ("external", Some(address)) | ("auto", Some(address)) => {
+            return Ok(Box::new(ExternalBackend::new(BackendAddress::parse(address)?)));
+        },
+        ("fpm", _) => Some(PhpServerSapi::FPM),
+        ("cgi", _) => Some(PhpServerSapi::CGI),
+        ("builtin", _) => Some(PhpServerSapi::CLI),
+        _ => None,
+    };
+
+    if address.is_some() {
+        warn!("The PHP address is only used by the external backend, ignoring it.");
+    }
+
+    let input = php_server::get_php_server_start_input(document_root, php_entrypoint_file, sapi)?;
+
+    Ok(Box::new(ManagedBackend::new(input)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn addresses_are_parsed_for_caddy() {
+        let upstream = |address: &str| BackendAddress::parse(address).unwrap().caddy_upstream();
+
+        assert_eq!(upstream("9000"), "127.0.0.1:9000");
+        assert_eq!(upstream("php:9000"), "php:9000");
+        assert_eq!(upstream("[::1]:9000"), "[::1]:9000");
+        assert_eq!(upstream("/var/run/php-fpm.sock"), "unix//var/run/php-fpm.sock");
+        assert_eq!(upstream("unix:/run/php.sock"), "unix//run/php.sock");
+        assert!(BackendAddress::parse("php:fpm").is_err());
+    }
+}
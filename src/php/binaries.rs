@@ -1,130 +1,472 @@
 use std::collections::HashMap;
-use std::collections::HashSet;
 use std::env;
+use std::ffi::OsString;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Result as FmtResult;
 use std::fs::read_to_string;
+use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 use std::process::Stdio;
 use std::str;
+use std::sync::Mutex;
+use std::thread;
 
+use dirs::home_dir;
 use glob::glob;
 use glob::GlobError;
 use is_executable::is_executable;
 use regex::Regex;
 
+use crate::config::config::clear_binaries_list;
 use crate::config::config::load_binaries_from_config;
+use crate::config::config::save_binaries_to_config;
+use crate::config::project_config;
+use crate::php::discovery;
 use crate::php::structs::PhpBinary;
 use crate::php::structs::PhpServerSapi;
 use crate::php::structs::PhpVersion;
-use std::ffi::OsString;
+use crate::php::version_constraint::VersionConstraint;
+use crate::php::version_constraint::VersionConstraintError;
+use crate::utils::project_directory::get_project_root;
 
-pub(crate) fn get_project_version() -> String {
-    let _binaries = all();
+pub(crate) const PHP_VERSION_FILE: &str = ".php-version";
 
-    let project_php_version_file_path = env::current_dir().unwrap().join(".php-version");
+/// Words that select a build flavor after a version constraint, like in "8.2 zts debug".
+const FLAVOR_WORDS: [&str; 3] = ["nts", "zts", "debug"];
 
-    let mut php_version = if project_php_version_file_path.exists() {
-        read_to_string(project_php_version_file_path).unwrap()
-    } else {
-        String::from("")
-    };
+/// What a binary tells about itself with the "--version" option.
+struct BinaryMetadata {
+    version: PhpVersion,
+    sapi: PhpServerSapi,
+    flavor: String,
+}
+
+/// Where the PHP version constraint of a project comes from, in order of precedence.
+pub(crate) struct ProjectVersionConstraint {
+    pub(crate) constraint: VersionConstraint,
+    pub(crate) raw: String,
+    pub(crate) source: &'static str,
+    pub(crate) selector: InstallationSelector,
+}
+
+/// Selects one of several PHP installations matching a version constraint, by path or build flavor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct InstallationSelector {
+    path: Option<PathBuf>,
+    flavor: Vec<String>,
+}
 
-    php_version = php_version.trim().to_string();
+impl InstallationSelector {
+    pub(crate) fn new(path: Option<&str>, flavor: Option<&str>) -> InstallationSelector {
+        let path = path.map(|path| {
+            let path = match path.strip_prefix('~') {
+                Some(relative_path) => home_dir().unwrap().join(relative_path.trim_start_matches(['/', '\\'])),
+                None => get_project_root().join(path),
+            };
+
+            // Binaries paths are canonicalized, except on Windows, where it adds the "\\?" prefix.
+            if cfg!(target_family = "windows") {
+                path
+            } else {
+                path.canonicalize().unwrap_or(path)
+            }
+        });
+
+        let flavor = flavor.unwrap_or("").split_whitespace().map(|word| word.to_lowercase()).collect();
 
-    if php_version != "" {
-        debug!("PHP version set to {} from \".php-version\" file.", php_version);
+        InstallationSelector { path, flavor }
     }
 
-    let mut system = String::from("");
-    let mut user_selected = String::from("");
-    let mut user_selected_version = String::from("");
+    pub(crate) fn is_empty(&self) -> bool {
+        self.path.is_none() && self.flavor.is_empty()
+    }
 
-    for (_version, _binary) in _binaries {
-        if php_version != "" && _version.version().starts_with(&php_version) {
-            if user_selected_version.eq("") || user_selected_version.as_str() < _version.version() {
-                user_selected_version = String::from(_version.version());
-                user_selected = _binary.preferred_sapi().to_string();
+    /// Completes this selector with the criteria of another one.
+    fn or(self, other: InstallationSelector) -> InstallationSelector {
+        InstallationSelector {
+            path: self.path.or(other.path),
+            flavor: if self.flavor.is_empty() { other.flavor } else { self.flavor },
+        }
+    }
+
+    /// The path can be the installation directory, one of its binaries, or the directory of one of its binaries.
+    pub(crate) fn matches(&self, binary: &PhpBinary) -> bool {
+        if let Some(path) = &self.path {
+            let binaries_paths = [binary.cli(), binary.fpm(), binary.cgi()];
+            let matches_binary = binaries_paths
+                .iter()
+                .filter(|binary_path| !binary_path.is_empty())
+                .map(|binary_path| Path::new(binary_path.as_str()))
+                .any(|binary_path| binary_path == path || binary_path.parent() == Some(path.as_path()));
+
+            if !matches_binary && Path::new(binary.directory()) != path {
+                return false;
             }
         }
-        if _binary.system() {
-            system = _binary.preferred_sapi().to_string();
+
+        let flavor = binary.flavor().to_lowercase();
+
+        self.flavor.iter().all(|word| flavor.split_whitespace().any(|flavor_word| flavor_word == word))
+    }
+}
+
+impl Display for InstallationSelector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let mut parts = self.flavor.clone();
+        if let Some(path) = &self.path {
+            parts.push(path.display().to_string());
         }
+
+        write!(f, "{}", parts.join(" "))
     }
+}
 
-    if user_selected.ne("") {
-        trace!("User selected version {}", user_selected_version);
-        return user_selected;
+/// Returns the PHP binary to use for the current project:
+/// the highest version matching the project's constraint if there is one, or else the system one.
+pub(crate) fn get_project_binary() -> Option<PhpBinary> {
+    let binaries = all();
+
+    if let Some(project_constraint) = get_project_version_constraint() {
+        match find_best_binary(&binaries, &project_constraint.constraint, &project_constraint.selector) {
+            Some(binary) => {
+                info!(
+                    "Using PHP {}, the highest installed version matching \"{}\" (from {}).",
+                    binary, project_constraint.raw, project_constraint.source
+                );
+                return Some(binary.clone());
+            },
+            None => warn!(
+                "No installed PHP version matches \"{}\" (from {}), falling back to the system PHP version.",
+                project_constraint.raw, project_constraint.source
+            ),
+        }
     }
-    if system.ne("") {
-        trace!("System version selected");
-        return system;
+
+    let system = binaries.into_iter().find(|binary| binary.system());
+
+    match &system {
+        Some(binary) => info!("Using system PHP version {}.", binary),
+        None => debug!("No system PHP version was found."),
+    }
+
+    system
+}
+
+/// Looks for a PHP version constraint in these places, in order:
+///  * The ".php-version" file,
+///  * The "config.platform.php" key of "composer.json", which selects the same minor version,
+///  * The "require.php" key of "composer.json".
+///
+/// The "[php]" table of the project configuration selects an installation among the matching ones,
+/// unless the ".php-version" file already does.
+pub(crate) fn get_project_version_constraint() -> Option<ProjectVersionConstraint> {
+    let php_config = project_config::load().php;
+    let config_selector = InstallationSelector::new(php_config.path.as_deref(), php_config.flavor.as_deref());
+
+    match get_php_version_file_constraint().or_else(get_composer_version_constraint) {
+        Some(mut project_constraint) => {
+            project_constraint.selector = project_constraint.selector.or(config_selector);
+
+            Some(project_constraint)
+        },
+        None if !config_selector.is_empty() => Some(ProjectVersionConstraint {
+            constraint: VersionConstraint::parse("*").unwrap(),
+            raw: config_selector.to_string(),
+            source: "\".rymfony.toml\" file",
+            selector: config_selector,
+        }),
+        None => None,
+    }
+}
+
+/// Parses a version selection, as written in the ".php-version" file. It can be:
+///  * A version constraint, optionally followed by a build flavor, like "8.2", "^8.1 zts" or "8.2.10 debug",
+///  * The path to an installation directory or to one of its binaries, like "/opt/php-debug".
+pub(crate) fn parse_version_selection(
+    raw: &str,
+) -> Result<(VersionConstraint, InstallationSelector), VersionConstraintError> {
+    let raw = raw.trim();
+
+    let is_path = raw.starts_with(['/', '\\', '~', '.']) || raw.get(1..2) == Some(":");
+    if is_path {
+        return Ok((VersionConstraint::parse("*")?, InstallationSelector::new(Some(raw), None)));
+    }
+
+    let (flavor, constraint): (Vec<&str>, Vec<&str>) =
+        raw.split_whitespace().partition(|word| FLAVOR_WORDS.contains(&word.to_lowercase().as_str()));
+
+    let constraint = if constraint.is_empty() { String::from("*") } else { constraint.join(" ") };
+
+    Ok((VersionConstraint::parse(&constraint)?, InstallationSelector::new(None, Some(&flavor.join(" ")))))
+}
+
+fn get_php_version_file_constraint() -> Option<ProjectVersionConstraint> {
+    let php_version_file_path = get_project_root().join(PHP_VERSION_FILE);
+    if !php_version_file_path.exists() {
+        return None;
+    }
+
+    let raw = read_to_string(&php_version_file_path).unwrap_or_default().trim().to_string();
+    if raw.is_empty() {
+        return None;
     }
 
-    "php".to_string()
+    match parse_version_selection(&raw) {
+        Ok((constraint, selector)) => {
+            Some(ProjectVersionConstraint { constraint, raw, source: "\".php-version\" file", selector })
+        },
+        Err(e) => {
+            warn!("Ignoring \".php-version\" file: {}", e);
+            None
+        },
+    }
 }
 
-pub(crate) fn all() -> HashMap<PhpVersion, PhpBinary> {
+pub(crate) fn get_composer_version_constraint() -> Option<ProjectVersionConstraint> {
+    let composer_json_path = get_project_root().join("composer.json");
+    if !composer_json_path.exists() {
+        return None;
+    }
+
+    let composer_json: serde_json::Value = match serde_json::from_str(&read_to_string(&composer_json_path).ok()?) {
+        Ok(json) => json,
+        Err(e) => {
+            warn!("Could not parse \"composer.json\" to find the PHP version: {}", e);
+            return None;
+        },
+    };
+
+    if let Some(raw) = composer_json.pointer("/config/platform/php").and_then(|value| value.as_str()) {
+        match VersionConstraint::same_minor_as(raw) {
+            Ok(constraint) => {
+                return Some(ProjectVersionConstraint {
+                    constraint,
+                    raw: raw.to_string(),
+                    source: "\"config.platform.php\" in \"composer.json\"",
+                    selector: InstallationSelector::default(),
+                })
+            },
+            Err(e) => warn!("Ignoring \"config.platform.php\" in \"composer.json\": {}", e),
+        }
+    }
+
+    if let Some(raw) = composer_json.pointer("/require/php").and_then(|value| value.as_str()) {
+        match VersionConstraint::parse(raw) {
+            Ok(constraint) => {
+                return Some(ProjectVersionConstraint {
+                    constraint,
+                    raw: raw.to_string(),
+                    source: "\"require.php\" in \"composer.json\"",
+                    selector: InstallationSelector::default(),
+                })
+            },
+            Err(e) => warn!("Ignoring \"require.php\" in \"composer.json\": {}", e),
+        }
+    }
+
+    None
+}
+
+/// Returns the installation with the highest version matching the constraint and the selector.
+/// Stable versions are preferred over pre-releases, like with Composer.
+/// Between installations of the same version, the system one is preferred, then non-debug builds,
+/// and then the first one that was found.
+pub(crate) fn find_best_binary<'a>(
+    binaries: &'a [PhpBinary],
+    constraint: &VersionConstraint,
+    selector: &InstallationSelector,
+) -> Option<&'a PhpBinary> {
+    binaries
+        .iter()
+        // "max_by" returns the last maximum element, so iterating backwards keeps the first one found.
+        .rev()
+        .filter(|binary| constraint.matches(binary.version().version()) && selector.matches(binary))
+        .max_by(|a, b| {
+            (!a.version().is_pre_release())
+                .cmp(&!b.version().is_pre_release())
+                .then_with(|| a.version().cmp(b.version()))
+                .then_with(|| a.system().cmp(&b.system()))
+                .then_with(|| b.is_debug().cmp(&a.is_debug()))
+        })
+}
+
+pub(crate) fn all() -> Vec<PhpBinary> {
     let load_infos = load_binaries_from_config();
-    return match load_infos {
-        Ok(data) => data,
+    match load_infos {
+        Ok(data) => {
+            let (mut binaries, mut changed) = revalidate(data);
+            changed |= mark_system_binary(&mut binaries, env::var_os("PATH"));
+            if changed {
+                save_binaries_to_config(&binaries);
+            }
+            binaries
+        },
         Err(_) => get_all(),
-    };
+    }
 }
 
-fn get_all() -> HashMap<PhpVersion, PhpBinary> {
-    let mut binaries: HashMap<PhpVersion, PhpBinary> = HashMap::new();
+/// Discovers the binaries again, and replaces the cached list.
+pub(crate) fn refresh() -> Vec<PhpBinary> {
+    if let Err(e) = clear_binaries_list() {
+        warn!("Could not clear binaries cache: {}", e);
+    }
+
+    let binaries = get_all();
+    save_binaries_to_config(&binaries);
+
+    binaries
+}
+
+/// Probes the binaries of a directory, or a single binary, like the ones added with "php:add".
+pub(crate) fn probe_path(path: &Path) -> Vec<PhpBinary> {
+    let paths = find_binaries_paths(path.to_path_buf());
+    let metadata = probe_binaries(&paths);
+
+    let mut binaries = binaries_from_paths(&paths, &metadata);
+    link_server_binaries(&mut binaries);
+
+    binaries
+}
 
-    binaries_from_rymfony_env(&mut binaries);
+fn get_all() -> Vec<PhpBinary> {
+    let mut directories: Vec<(String, Vec<String>)> = Vec::new();
 
-    // We don't want to use these PATH by default on MacOS "Big Sur",
-    // because Apple added a deprecation to the default binary which changes
-    // how the PHP version is displayed when running `php --version`.
-    if !is_macos_big_sur() {
-        binaries_from_env(&mut binaries);
-        merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("/usr/bin")));
-        merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("/usr/sbin")));
+    for source in discovery::sources() {
+        for dir in source.directories() {
+            trace!("Checking {} path for PHP binaries: {}", source.name(), dir.to_str().unwrap());
+            directories.push((source.name().to_string(), find_binaries_paths(dir)));
+        }
     }
 
-    merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("/usr/local/Cellar/php/*/bin")));
-    merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("/usr/local/Cellar/php/*/sbin")));
-    merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("/usr/local/Cellar/php@*/*/bin")));
-    merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("/usr/local/Cellar/php@*/*/sbin")));
-    merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("/usr/local/php*/bin")));
-    if cfg!(target_family = "windows") {
-        merge_binaries(&mut binaries, binaries_from_dir(PathBuf::from("c:\\php")));
+    let mut paths: Vec<String> = directories.iter().flat_map(|(_, paths)| paths.iter().cloned()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let metadata = probe_binaries(&paths);
+
+    let mut binaries: Vec<PhpBinary> = Vec::new();
+
+    for (source, paths) in directories {
+        let mut found = binaries_from_paths(&paths, &metadata);
+        for binary in found.iter_mut() {
+            binary.set_source(&source);
+        }
+
+        merge_binaries(&mut binaries, found);
     }
 
+    link_server_binaries(&mut binaries);
+    mark_system_binary(&mut binaries, env::var_os("PATH"));
+
+    probe_builds(binaries.iter_mut());
+
     binaries
 }
 
-fn binaries_from_env(binaries: &mut HashMap<PhpVersion, PhpBinary>) {
-    let path_string = env::var_os("PATH").unwrap();
-    let path_dirs = path_string
-        .to_str()
-        .unwrap()
-        .split(if cfg!(target_family = "windows") { ";" } else { ":" })
-        .collect::<Vec<&str>>();
+/// Checks that the cached binaries were not changed or removed since they were probed.
+/// Changed binaries are probed again, and removed ones are dropped from the list.
+/// Returns whether the list changed.
+fn revalidate(cached: Vec<PhpBinary>) -> (Vec<PhpBinary>, bool) {
+    let mut binaries: Vec<PhpBinary> = Vec::new();
+    let mut changed_paths: Vec<(String, String)> = Vec::new();
+    let mut changed = false;
+
+    for mut binary in cached {
+        for sapi in PhpServerSapi::all() {
+            let path = binary.sapi_path(&sapi);
+            if path.is_empty() || binary.is_unchanged(&path) {
+                continue;
+            }
+
+            binary.remove_sapi(&sapi);
+            changed = true;
+
+            if Path::new(&path).exists() {
+                debug!("PHP binary \"{}\" changed since it was probed, probing it again.", path);
+                changed_paths.push((path, binary.source().clone()));
+            } else {
+                debug!("PHP binary \"{}\" does not exist anymore, removing it from cache.", path);
+            }
+        }
 
-    for dir in path_dirs {
-        trace!("Checking path for PHP binaries: {}", &dir);
-        merge_binaries(binaries, binaries_from_dir(PathBuf::from(dir)));
+        if PhpServerSapi::all().iter().any(|sapi| binary.has_sapi(sapi)) {
+            binaries.push(binary);
+        }
     }
-}
 
-fn binaries_from_rymfony_env(binaries: &mut HashMap<PhpVersion, PhpBinary>) {
-    let path_string = env::var_os("RYMFONY_PATH").unwrap_or(OsString::from(""));
-    let path_dirs = path_string
-        .to_str()
-        .unwrap()
-        .split(if cfg!(target_family = "windows") { ";" } else { ":" })
-        .collect::<Vec<&str>>();
+    if !changed {
+        return (binaries, false);
+    }
+
+    let paths: Vec<String> = changed_paths.iter().map(|(path, _)| path.clone()).collect();
+    let metadata = probe_binaries(&paths);
+
+    let mut probed_paths: Vec<String> = Vec::new();
+
+    for (path, source) in changed_paths {
+        let metadata = match metadata.get(&path) {
+            Some(found) => found,
+            None => continue,
+        };
 
-    for dir in path_dirs {
-        merge_binaries(binaries, binaries_from_dir(PathBuf::from(dir)));
+        let index = add_to_installation(&mut binaries, &path, metadata);
+        if binaries[index].source().is_empty() {
+            binaries[index].set_source(&source);
+        }
+
+        probed_paths.push(path);
     }
+
+    link_server_binaries(&mut binaries);
+
+    probe_builds(
+        binaries
+            .iter_mut()
+            .filter(|binary| PhpServerSapi::all().iter().any(|sapi| probed_paths.contains(&binary.sapi_path(sapi)))),
+    );
+
+    (binaries, true)
+}
+
+/// Executes the binaries in parallel to get their version and SAPI, since some of them can be slow to start.
+fn probe_binaries(paths: &[String]) -> HashMap<String, BinaryMetadata> {
+    let metadata = Mutex::new(HashMap::new());
+
+    for_each_in_parallel(paths.iter().collect(), |path: &String| {
+        if let Ok(found) = get_binary_metadata(path) {
+            metadata.lock().unwrap().insert(path.clone(), found);
+        }
+    });
+
+    metadata.into_inner().unwrap()
+}
+
+fn probe_builds<'a>(binaries: impl Iterator<Item = &'a mut PhpBinary>) {
+    for_each_in_parallel(binaries.collect(), |binary: &mut PhpBinary| binary.probe_build());
+}
+
+/// Probing starts a process per binary, so the number of processes running at once is limited to the CPU count,
+/// which matters for directories containing hundreds of binaries, like the Nix store.
+fn for_each_in_parallel<T: Send>(items: Vec<T>, probe: impl Fn(T) + Sync) {
+    let workers = thread::available_parallelism().map_or(4, |count| count.get()).min(items.len());
+    let queue = Mutex::new(items.into_iter());
+
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let item = queue.lock().unwrap().next();
+                match item {
+                    Some(item) => probe(item),
+                    None => break,
+                }
+            });
+        }
+    });
 }
 
-fn binaries_from_dir(path: PathBuf) -> HashMap<PhpVersion, PhpBinary> {
+fn find_binaries_paths(path: PathBuf) -> Vec<String> {
     // This matches executables like "php", "php74", "php7.4", "php-fpm", "php7.4-cgi" or "php-fpm74"
     let binaries_regex = if cfg!(target_family = "windows") {
         // On Windows, we mostly have "php" and "php-cgi"
@@ -134,6 +476,14 @@ fn binaries_from_dir(path: PathBuf) -> HashMap<PhpVersion, PhpBinary> {
         Regex::new(r"php(\d+(\.\d+)*)?([_-]?fpm|[_-]?cgi)?(\d+(\.\d+)*)?$").unwrap()
     };
 
+    // A binary added with "php:add" is probed whatever its name, which is checked by probing it.
+    if path.is_file() {
+        #[cfg(not(target_family = "windows"))]
+        let path: PathBuf = path.canonicalize().unwrap_or(path);
+
+        return vec![path.to_str().unwrap().to_string()];
+    }
+
     let mut binaries_paths: Vec<String> = Vec::new();
 
     let mut path = path.display().to_string();
@@ -148,14 +498,14 @@ fn binaries_from_dir(path: PathBuf) -> HashMap<PhpVersion, PhpBinary> {
         let glob_path_exe = format!("{}/php*.exe", path);
         let glob_path_cmd = format!("{}/php*.cmd", path);
         let glob_path_bat = format!("{}/php*.bat", path);
-        glob(&glob_path_exe.as_str())
+        glob(glob_path_exe.as_str())
             .unwrap()
-            .chain(glob(&glob_path_cmd.as_str()).unwrap())
-            .chain(glob(&glob_path_bat.as_str()).unwrap())
+            .chain(glob(glob_path_cmd.as_str()).unwrap())
+            .chain(glob(glob_path_bat.as_str()).unwrap())
             .collect()
     } else {
         let glob_path = format!("{}/php*", path);
-        glob(&glob_path.as_str()).unwrap().collect()
+        glob(glob_path.as_str()).unwrap().collect()
     };
 
     for entry in entries {
@@ -185,86 +535,386 @@ fn binaries_from_dir(path: PathBuf) -> HashMap<PhpVersion, PhpBinary> {
         binaries_paths.push(binary.to_str().unwrap().parse().unwrap());
     }
 
-    let binaries_paths: HashSet<String> = binaries_paths.iter().cloned().collect();
+    binaries_paths.sort();
+    binaries_paths.dedup();
 
-    let mut binaries: HashMap<PhpVersion, PhpBinary> = HashMap::new();
+    binaries_paths
+}
 
-    for path in binaries_paths.iter() {
-        let binary = get_binary_metadata(&path);
+fn binaries_from_paths(paths: &[String], metadata: &HashMap<String, BinaryMetadata>) -> Vec<PhpBinary> {
+    let mut binaries: Vec<PhpBinary> = Vec::new();
 
-        if binary.is_err() {
-            continue;
+    for path in paths.iter() {
+        if let Some(metadata) = metadata.get(path) {
+            add_to_installation(&mut binaries, path, metadata);
         }
+    }
 
-        let (version, sapi) = binary.unwrap();
+    binaries
+}
 
-        if binaries.contains_key(&version) {
-            let current = &mut binaries.get_mut(&version).unwrap();
+/// Adds the binary to the installation it belongs to, which is created if needed.
+/// Returns the index of the installation.
+fn add_to_installation(binaries: &mut Vec<PhpBinary>, path: &str, metadata: &BinaryMetadata) -> usize {
+    let installation = PhpBinary::new(metadata.version.clone(), &get_installation_directory(path), &metadata.flavor);
+
+    let index = match binaries.iter().position(|binary| binary.is_same_installation(&installation)) {
+        Some(index) => index,
+        None => {
+            binaries.push(installation);
+            binaries.len() - 1
+        },
+    };
 
-            if !current.has_sapi(&sapi) {
-                current.add_sapi(&sapi, &path);
-            }
-        } else {
-            let mut bin = PhpBinary::from_version(version.clone());
-            bin.add_sapi(&sapi, &path);
-            let _ = &binaries.insert(version.clone(), bin);
-        }
+    if !binaries[index].has_sapi(&metadata.sapi) {
+        binaries[index].add_sapi(&metadata.sapi, &path.to_string());
     }
 
-    binaries
+    index
+}
+
+/// Returns the installation prefix of a binary, so that binaries installed in "bin" and "sbin" are grouped together.
+fn get_installation_directory(path: &str) -> String {
+    let parent = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+    let directory = match parent.file_name().and_then(|name| name.to_str()) {
+        Some("bin") | Some("sbin") => parent.parent().unwrap_or(parent),
+        _ => parent,
+    };
+
+    directory.to_str().unwrap().to_string()
 }
 
-fn get_binary_metadata(binary: &str) -> Result<(PhpVersion, PhpServerSapi), ()> {
-    let process_result = Command::new(binary).arg("--version").stdout(Stdio::piped()).spawn();
+fn get_binary_metadata(binary: &str) -> Result<BinaryMetadata, ()> {
+    let output = match Command::new(binary).arg("--version").stdout(Stdio::piped()).output() {
+        Ok(output) => output,
+        Err(_) => {
+            debug!("Path \"{}\" was detected, but is not a valid PHP binary.", &binary);
+
+            return Err(());
+        },
+    };
+
+    let output = String::from_utf8_lossy(&output.stdout);
+
+    let (version, sapi_name, flavor) = match parse_version_output(&output) {
+        Some(parsed) => parsed,
+        None => {
+            warn!("Skipping PHP binary \"{}\": unexpected version output \"{}\".", &binary, output.trim());
 
-    if process_result.is_err() {
-        debug!("Path \"{}\" was detected, but is not a valid PHP binary.", &binary);
+            return Err(());
+        },
+    };
+
+    let version = match version.parse::<PhpVersion>() {
+        Ok(version) => version,
+        Err(e) => {
+            warn!("Skipping PHP binary \"{}\": {}", &binary, e);
+
+            return Err(());
+        },
+    };
+
+    let sapi = PhpServerSapi::from_str(&sapi_name);
+    if sapi == PhpServerSapi::Unknown {
+        warn!("Skipping PHP binary \"{}\": the \"{}\" SAPI is not supported.", &binary, &sapi_name);
 
         return Err(());
     }
 
-    let process = process_result.unwrap();
+    Ok(BinaryMetadata { version, sapi, flavor })
+}
+
+/// Returns the version, the SAPI name and the build flavor displayed by "php --version".
+fn parse_version_output(output: &str) -> Option<(String, String, String)> {
+    // Some binaries display warnings before their version, like the deprecated PHP of macOS.
+    // The build flavor is displayed after the build date, like "(built: Jan 1 2023 00:00:00) (ZTS DEBUG)".
+    // Windows builds add the compiler and architecture, like "(ZTS Visual C++ 2019 x64 DEBUG)",
+    // and PHP 7 surrounds it with spaces, like "( ZTS )".
+    let php_version_output_regex = Regex::new(r"(?m)^PHP (\S+) \(([^)]+)\)(?:.*\( ?(NTS|ZTS)([^)]*)\))?").unwrap();
+
+    let capts = php_version_output_regex.captures(output)?;
+
+    let mut flavor = capts.get(3).map_or("NTS", |thread_safety| thread_safety.as_str()).to_string();
+    if capts.get(4).is_some_and(|details| details.as_str().trim_end().ends_with(" DEBUG")) {
+        flavor.push_str(" debug");
+    }
+
+    Some((capts[1].to_string(), capts[2].to_string(), flavor))
+}
+
+fn merge_binaries(into: &mut Vec<PhpBinary>, from: Vec<PhpBinary>) {
+    for binary in from {
+        match into.iter_mut().find(|existing| existing.is_same_installation(&binary)) {
+            Some(existing) => existing.merge_with(binary),
+            None => into.push(binary),
+        }
+    }
+}
+
+/// Installations are grouped by directory, so FPM or CGI binaries installed elsewhere than their CLI,
+/// like in another Nix store path, end up in an installation without CLI.
+/// These are linked to the only installation having a CLI with the same version and build flavor, if there is one.
+fn link_server_binaries(binaries: &mut Vec<PhpBinary>) {
+    let mut index = 0;
 
-    let output = process.wait_with_output().unwrap();
-    let stdout = output.stdout;
-    let output = String::from_utf8(stdout).unwrap();
+    while index < binaries.len() {
+        let server = &binaries[index];
 
-    let php_version_output_regex = Regex::new(r"^PHP (\d\.\d+\.\d+)[^ ]* \(([^)]+)\)").unwrap();
+        let candidates: Vec<usize> = if server.has_sapi(&PhpServerSapi::CLI) {
+            Vec::new()
+        } else {
+            binaries
+                .iter()
+                .enumerate()
+                .filter(|(_, binary)| {
+                    binary.has_sapi(&PhpServerSapi::CLI)
+                        && binary.version() == server.version()
+                        && binary.flavor() == server.flavor()
+                        && PhpServerSapi::all().iter().all(|sapi| !(binary.has_sapi(sapi) && server.has_sapi(sapi)))
+                })
+                .map(|(candidate_index, _)| candidate_index)
+                .collect()
+        };
+
+        if candidates.len() != 1 {
+            index += 1;
+            continue;
+        }
 
-    if !php_version_output_regex.is_match(&output) {
-        panic!("Version \"{}\" for PHP binary \"{}\" is invalid.", &output, &binary);
+        let server = binaries.remove(index);
+        let cli_index = if candidates[0] > index { candidates[0] - 1 } else { candidates[0] };
+
+        debug!("Linking PHP {} to the CLI of PHP {}.", server, binaries[cli_index]);
+
+        binaries[cli_index].merge_with(server);
     }
+}
+
+/// Marks the installation of the "php" command executed from a terminal as the system one.
+/// Returns whether the system installation changed.
+fn mark_system_binary(binaries: &mut [PhpBinary], path_env: Option<OsString>) -> bool {
+    let system_php = find_system_php(path_env);
+
+    match &system_php {
+        Some(path) => debug!("System PHP binary resolved to \"{}\".", path.to_str().unwrap()),
+        None => debug!("No \"php\" binary was found in the PATH."),
+    }
+
+    let mut changed = false;
+
+    for binary in binaries.iter_mut() {
+        let is_system = system_php.as_ref().is_some_and(|path| is_same_path(path, binary.cli()));
 
-    let capts = php_version_output_regex.captures(&output).unwrap();
-    let version = &capts[1];
-    let sapi = &capts[2];
+        if binary.system() != is_system {
+            binary.set_system(is_system);
+            changed = true;
+        }
+    }
 
-    Ok((PhpVersion::from_str(version), PhpServerSapi::from_str(&sapi)))
+    changed
 }
 
-fn merge_binaries(into: &mut HashMap<PhpVersion, PhpBinary>, from: HashMap<PhpVersion, PhpBinary>) {
-    for (version, mut binary) in from {
-        // FIXME
-        // this needs to be fixed, but for now we assume that the first ever found version is
-        // the one that is first in PATH and therefore the "system" binary
-        let _ = &binary.set_system(if into.len() == 0 { true } else { false });
+/// Resolves the "php" command like a shell does, with the first matching directory of the PATH,
+/// and follows symlinks, like "/usr/bin/php" to "/etc/alternatives/php" to "/usr/bin/php8.2" on Debian and RHEL.
+fn find_system_php(path_env: Option<OsString>) -> Option<PathBuf> {
+    let php = which::which_in("php", path_env, env::current_dir().ok()?).ok().or_else(|| {
+        let alternative = Path::new("/etc/alternatives/php");
 
-        if into.contains_key(&version) {
-            into.get_mut(&version).unwrap().merge_with(binary);
+        if alternative.exists() {
+            Some(alternative.to_path_buf())
         } else {
-            into.insert(version, binary);
+            None
         }
+    })?;
+
+    // Canonicalize on Windows leaves the "\\?" prefix on canonicalized paths, like for the binaries paths.
+    if cfg!(target_family = "windows") {
+        Some(php)
+    } else {
+        php.canonicalize().ok()
     }
 }
 
-fn is_macos_big_sur() -> bool {
-    if cfg!(not(target_os = "macos")) {
-        return false;
+fn is_same_path(path: &Path, binary_path: &str) -> bool {
+    if cfg!(target_family = "windows") {
+        path.to_str().unwrap().eq_ignore_ascii_case(binary_path)
+    } else {
+        path == Path::new(binary_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::remove_file;
+    use std::fs::write;
+
+    #[test]
+    fn parallel_probes_are_limited_to_the_cpu_count() {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::atomic::Ordering;
+
+        let (running, max_running, probed) = (AtomicUsize::new(0), AtomicUsize::new(0), AtomicUsize::new(0));
+
+        for_each_in_parallel((0..64).collect(), |_: i32| {
+            let count = running.fetch_add(1, Ordering::SeqCst) + 1;
+            max_running.fetch_max(count, Ordering::SeqCst);
+            thread::sleep(std::time::Duration::from_millis(2));
+            running.fetch_sub(1, Ordering::SeqCst);
+            probed.fetch_add(1, Ordering::SeqCst);
+        });
+
+        assert_eq!(probed.into_inner(), 64);
+        assert!(max_running.into_inner() <= thread::available_parallelism().map_or(4, |count| count.get()));
+    }
+
+    #[test]
+    fn build_flavor_is_read_from_the_version_output() {
+        let flavor = |output: &str| parse_version_output(output).map(|(_, _, flavor)| flavor);
+
+        assert_eq!(
+            parse_version_output("PHP 8.2.10 (cli) (built: Sep  2 2023 06:59:22) (NTS)\nCopyright (c) The PHP Group\n"),
+            Some((String::from("8.2.10"), String::from("cli"), String::from("NTS")))
+        );
+        assert_eq!(
+            flavor("PHP 8.3.0 (fpm-fcgi) (built: Nov 23 2023 10:00:00) (ZTS DEBUG)").as_deref(),
+            Some("ZTS debug")
+        );
+        assert_eq!(
+            flavor(
+                "PHP 8.2.12 (cli) (built: Oct 24 2023 21:15:15) (ZTS Visual C++ 2019 x64)\nCopyright (c) The PHP Group"
+            )
+            .as_deref(),
+            Some("ZTS")
+        );
+        assert_eq!(
+            flavor("PHP 8.2.12 (cgi-fcgi) (built: Oct 24 2023 21:15:15) (NTS Visual C++ 2019 x64 DEBUG)").as_deref(),
+            Some("NTS debug")
+        );
+        assert_eq!(flavor("PHP 7.4.33 (cli) (built: Nov  8 2022 11:40:57) ( NTS )").as_deref(), Some("NTS"));
+        assert_eq!(
+            flavor("PHP 7.4.33 (cli) (built: Nov  8 2022 11:40:57) ( ZTS DEBUG )").as_deref(),
+            Some("ZTS debug")
+        );
+        assert_eq!(parse_version_output("Not PHP"), None);
     }
 
-    let os_infos = os_info::get();
-    let os_version = os_infos.version();
-    let bigsur_version = "11.0.0";
+    #[test]
+    fn revalidate_drops_removed_binaries() {
+        let kept = env::temp_dir().join("rymfony_revalidate_test_kept");
+        let removed = env::temp_dir().join("rymfony_revalidate_test_removed");
+        write(&kept, "").unwrap();
+        write(&removed, "").unwrap();
+
+        let mut cached = Vec::new();
+        for (version, path) in [("8.1.0", &kept), ("8.2.0", &removed)] {
+            let mut binary = PhpBinary::new(version.parse::<PhpVersion>().unwrap(), "/usr", "NTS");
+            binary.add_sapi(&PhpServerSapi::CLI, &path.to_str().unwrap().to_string());
+            cached.push(binary);
+        }
+
+        let (binaries, changed) = revalidate(cached.clone());
+        assert!(!changed);
+        assert_eq!(binaries.len(), 2);
+
+        remove_file(&removed).unwrap();
+        let (binaries, changed) = revalidate(cached);
+        remove_file(&kept).unwrap();
+
+        assert!(changed);
+        assert_eq!(binaries.len(), 1);
+        assert_eq!(binaries[0].version().version(), "8.1.0");
+    }
 
-    return version_compare::compare_to(&os_version.to_string(), &bigsur_version, version_compare::Cmp::Gt).unwrap();
+    #[test]
+    fn installations_of_the_same_version_are_selected_by_path_or_flavor() {
+        let metadata = |version: &str, sapi: PhpServerSapi, flavor: &str| BinaryMetadata {
+            version: version.parse::<PhpVersion>().unwrap(),
+            sapi,
+            flavor: flavor.to_string(),
+        };
+
+        let mut binaries = Vec::new();
+        add_to_installation(&mut binaries, "/usr/bin/php8.2", &metadata("8.2.10", PhpServerSapi::CLI, "NTS"));
+        add_to_installation(&mut binaries, "/usr/sbin/php-fpm8.2", &metadata("8.2.10", PhpServerSapi::FPM, "NTS"));
+        add_to_installation(
+            &mut binaries,
+            "/opt/php-debug/bin/php",
+            &metadata("8.2.10", PhpServerSapi::CLI, "NTS debug"),
+        );
+        add_to_installation(&mut binaries, "/opt/php-zts/php", &metadata("8.2.10", PhpServerSapi::CLI, "ZTS"));
+
+        assert_eq!(binaries.len(), 3);
+        assert_eq!(binaries[0].directory(), "/usr");
+        assert_eq!(binaries[0].fpm(), "/usr/sbin/php-fpm8.2");
+
+        let select = |raw: &str| {
+            let (constraint, selector) = parse_version_selection(raw).unwrap();
+            find_best_binary(&binaries, &constraint, &selector).map(|binary| binary.directory().as_str())
+        };
+
+        assert_eq!(select("8.2"), Some("/usr"));
+        assert_eq!(select("8.2 debug"), Some("/opt/php-debug"));
+        assert_eq!(select("^8.1 ZTS"), Some("/opt/php-zts"));
+        assert_eq!(select("/opt/php-debug"), Some("/opt/php-debug"));
+        assert_eq!(select("/opt/php-zts/php"), Some("/opt/php-zts"));
+        assert_eq!(select("/usr/sbin"), Some("/usr"));
+        assert_eq!(select("8.3 debug"), None);
+    }
+
+    #[test]
+    fn server_binaries_are_linked_to_the_cli_of_the_same_build() {
+        let binary = |directory: &str, flavor: &str, sapi: PhpServerSapi| {
+            let mut binary = PhpBinary::new("8.2.10".parse::<PhpVersion>().unwrap(), directory, flavor);
+            binary.add_sapi(&sapi, &format!("{}/bin/php-{}", directory, sapi));
+            binary
+        };
+
+        let mut binaries = vec![
+            binary("/nix/store/php-fpm", "NTS", PhpServerSapi::FPM),
+            binary("/nix/store/php", "NTS", PhpServerSapi::CLI),
+            binary("/nix/store/php-zts", "ZTS", PhpServerSapi::CLI),
+            binary("/nix/store/php-cgi-debug", "NTS debug", PhpServerSapi::CGI),
+        ];
+
+        link_server_binaries(&mut binaries);
+
+        assert_eq!(binaries.len(), 3);
+        assert_eq!(binaries[0].directory(), "/nix/store/php");
+        assert_eq!(binaries[0].fpm(), "/nix/store/php-fpm/bin/php-FPM");
+        assert!(!binaries[1].has_sapi(&PhpServerSapi::FPM));
+        assert_eq!(binaries[2].directory(), "/nix/store/php-cgi-debug");
+    }
+
+    #[cfg(not(target_family = "windows"))]
+    #[test]
+    fn system_binary_is_resolved_through_path_and_symlinks() {
+        let directory = env::temp_dir().join("rymfony_system_php_test");
+        let bin = directory.join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+
+        let real_php = bin.join("php8.2");
+        write(&real_php, "").unwrap();
+        std::fs::set_permissions(&real_php, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+        let _ = remove_file(bin.join("php"));
+        std::os::unix::fs::symlink(&real_php, bin.join("php")).unwrap();
+
+        let real_php = real_php.canonicalize().unwrap().to_str().unwrap().to_string();
+        let mut binaries: Vec<PhpBinary> = ["8.1.0", "8.2.0"]
+            .iter()
+            .map(|version| PhpBinary::new(version.parse::<PhpVersion>().unwrap(), "/usr", "NTS"))
+            .collect();
+        binaries[0].add_sapi(&PhpServerSapi::CLI, &String::from("/usr/bin/php8.1"));
+        binaries[1].add_sapi(&PhpServerSapi::CLI, &real_php);
+
+        let path_env = env::join_paths([Path::new("/nonexistent"), bin.as_path()]).unwrap();
+        let changed = mark_system_binary(&mut binaries, Some(path_env));
+
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        assert!(changed);
+        assert!(!binaries[0].system());
+        assert!(binaries[1].system());
+    }
 }
@@ -0,0 +1,137 @@
+use serde::Deserialize;
+use serde::Serialize;
+use std::process::Command;
+
+/// Details about how a PHP binary was built and configured, as reported by "php -m" and "php -i".
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(default)]
+pub(crate) struct PhpBuildInfo {
+    pub(crate) extensions: Vec<String>,
+    pub(crate) thread_safe: bool,
+    pub(crate) debug: bool,
+    pub(crate) loaded_ini: Option<String>,
+    pub(crate) additional_ini: Vec<String>,
+}
+
+/// Executes the binary to get its build details.
+/// Returns None if the binary could not be executed, so that the binary is still usable without these details.
+pub(crate) fn probe(binary: &str) -> Option<PhpBuildInfo> {
+    let modules = run(binary, "-m")?;
+    let info = run(binary, "-i")?;
+
+    let mut build_info = parse_info(&info);
+    build_info.extensions = parse_modules(&modules);
+
+    Some(build_info)
+}
+
+fn run(binary: &str, option: &str) -> Option<String> {
+    match Command::new(binary).arg(option).output() {
+        Ok(output) if output.status.success() => Some(String::from_utf8_lossy(&output.stdout).to_string()),
+        Ok(output) => {
+            debug!("\"{} {}\" exited with {}.", binary, option, output.status);
+            None
+        },
+        Err(e) => {
+            debug!("Could not execute \"{} {}\": {}", binary, option, e);
+            None
+        },
+    }
+}
+
+/// Parses the output of "php -m", which lists PHP modules and Zend modules in two sections.
+fn parse_modules(output: &str) -> Vec<String> {
+    let mut extensions: Vec<String> = output
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('['))
+        .map(|line| line.to_string())
+        .collect();
+
+    extensions.sort_by_key(|extension| extension.to_lowercase());
+    extensions.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+
+    extensions
+}
+
+/// Parses the text output of "php -i".
+fn parse_info(output: &str) -> PhpBuildInfo {
+    let mut build_info = PhpBuildInfo::default();
+    let mut lines = output.lines();
+
+    while let Some(line) = lines.next() {
+        let (name, value) = match line.split_once(" => ") {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => continue,
+        };
+
+        match name {
+            "Thread Safety" => build_info.thread_safe = value == "enabled",
+            "Debug Build" => build_info.debug = value == "yes",
+            "Loaded Configuration File" if value != "(none)" => build_info.loaded_ini = Some(value.to_string()),
+            "Additional .ini files parsed" if value != "(none)" => {
+                // The list of files continues on the next lines, as long as they end with a comma.
+                let mut files = value.to_string();
+                while files.ends_with(',') {
+                    match lines.next() {
+                        Some(next) => files.push_str(next.trim()),
+                        None => break,
+                    }
+                }
+                build_info.additional_ini =
+                    files.split(',').map(|file| file.trim().to_string()).filter(|file| !file.is_empty()).collect();
+            },
+            _ => {},
+        }
+    }
+
+    build_info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modules_are_merged_and_sorted() {
+        let output =
+            "[PHP Modules]\nCore\nintl\nPDO\npdo_pgsql\nxdebug\nZend OPcache\n\n[Zend Modules]\nXdebug\nZend OPcache\n";
+
+        assert_eq!(parse_modules(output), vec!["Core", "intl", "PDO", "pdo_pgsql", "xdebug", "Zend OPcache"]);
+    }
+
+    #[test]
+    fn info_contains_build_and_ini_files() {
+        let output = "phpinfo()
+PHP Version => 8.2.10
+
+System => Linux
+Loaded Configuration File => /etc/php/8.2/cli/php.ini
+Scan this dir for additional .ini files => /etc/php/8.2/cli/conf.d
+Additional .ini files parsed => /etc/php/8.2/cli/conf.d/10-opcache.ini,
+/etc/php/8.2/cli/conf.d/20-intl.ini
+
+PHP API => 20220829
+Debug Build => no
+Thread Safety => enabled
+";
+
+        let build_info = parse_info(output);
+
+        assert!(build_info.thread_safe);
+        assert!(!build_info.debug);
+        assert_eq!(build_info.loaded_ini.as_deref(), Some("/etc/php/8.2/cli/php.ini"));
+        assert_eq!(
+            build_info.additional_ini,
+            vec!["/etc/php/8.2/cli/conf.d/10-opcache.ini", "/etc/php/8.2/cli/conf.d/20-intl.ini"]
+        );
+    }
+
+    #[test]
+    fn no_ini_file() {
+        let build_info = parse_info("Loaded Configuration File => (none)\nAdditional .ini files parsed => (none)\n");
+
+        assert_eq!(build_info.loaded_ini, None);
+        assert!(build_info.additional_ini.is_empty());
+    }
+}
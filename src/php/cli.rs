@@ -0,0 +1,97 @@
+use crate::php::binaries;
+use crate::php::environment;
+use crate::utils::project_directory::get_project_root;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::process::Command;
+use std::process::ExitCode;
+use std::process::ExitStatus;
+
+#[derive(Debug)]
+struct PhpCliError(String);
+
+impl fmt::Display for PhpCliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "An error occured: {}", self.0)
+    }
+}
+
+impl Error for PhpCliError {}
+
+/// Returns a command executing the project's PHP CLI, with the same environment as the PHP server.
+/// Standard input and outputs are inherited from the current process.
+pub(crate) fn get_php_cli_command() -> Result<Command, Box<dyn Error>> {
+    let binary = binaries::get_project_binary().ok_or_else(|| {
+        PhpCliError(String::from(
+            "No PHP binary found for this project. You can execute \"rymfony php:list --refresh\" to update binaries paths cache.",
+        ))
+    })?;
+
+    if binary.cli().is_empty() {
+        return Err(Box::new(PhpCliError(format!("No PHP CLI binary was found for PHP {}.", binary))));
+    }
+
+    let mut command = Command::new(binary.cli());
+
+    environment::apply(&mut command, &binary);
+
+    Ok(command)
+}
+
+/// Returns a command executing Composer with the project's PHP CLI.
+pub(crate) fn get_composer_command() -> Result<Command, Box<dyn Error>> {
+    let mut command = get_php_cli_command()?;
+    let composer_path = get_composer_path()?;
+
+    debug!("Composer detected at {}", composer_path.to_str().unwrap());
+
+    command.arg(composer_path);
+
+    Ok(command)
+}
+
+/// Looks for a "composer.phar" file in the project first, and then for Composer in the PATH.
+fn get_composer_path() -> Result<PathBuf, Box<dyn Error>> {
+    let project_composer = get_project_root().join("composer.phar");
+    if project_composer.is_file() {
+        return Ok(project_composer);
+    }
+
+    let composer = which::which("composer")
+        .map_err(|_| PhpCliError(String::from("Composer was not found in the PATH, nor in the project directory.")))?;
+
+    // On Windows, Composer's installer creates a "composer.bat" wrapper next to the real "composer.phar" file.
+    let extension = composer.extension().map(|extension| extension.to_ascii_lowercase());
+    if extension.is_some() && extension != Some("phar".into()) {
+        let phar = composer.with_file_name("composer.phar");
+        if phar.is_file() {
+            return Ok(phar);
+        }
+
+        return Err(Box::new(PhpCliError(format!(
+            "Composer was found at \"{}\", but it cannot be executed with PHP.",
+            composer.to_str().unwrap()
+        ))));
+    }
+
+    Ok(composer)
+}
+
+/// Converts the status of a finished PHP process to the exit code of the current command.
+pub(crate) fn to_exit_code(status: io::Result<ExitStatus>) -> ExitCode {
+    match status {
+        Ok(status) => match status.code() {
+            // Codes that do not fit in an exit code, like Windows NTSTATUS values, must not turn into a success.
+            Some(code) => ExitCode::from(u8::try_from(code).unwrap_or(1)),
+            None => ExitCode::FAILURE,
+        },
+        Err(e) => {
+            error!("Could not execute PHP: {}", e);
+
+            ExitCode::from(1)
+        },
+    }
+}
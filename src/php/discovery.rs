@@ -0,0 +1,286 @@
+use crate::config::global_config;
+use dirs::home_dir;
+use glob::glob;
+use glob::Pattern;
+use std::cmp::Reverse;
+use std::env;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// A place where PHP binaries can be installed, like a package manager or a version manager.
+pub(crate) trait DiscoverySource {
+    /// Name displayed in "php:list" for the binaries found by this source.
+    fn name(&self) -> &str;
+
+    /// Directories that may contain PHP binaries, in order of precedence.
+    fn directories(&self) -> Vec<PathBuf>;
+}
+
+/// Directories listed in an environment variable, like "PATH".
+struct EnvironmentVariable(&'static str);
+
+impl DiscoverySource for EnvironmentVariable {
+    fn name(&self) -> &str {
+        self.0
+    }
+
+    fn directories(&self) -> Vec<PathBuf> {
+        match env::var_os(self.0) {
+            Some(paths) => env::split_paths(&paths).filter(|path| !path.as_os_str().is_empty()).collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Directories and binaries added with the "php:add" command.
+struct ConfiguredPaths(Vec<PathBuf>);
+
+impl DiscoverySource for ConfiguredPaths {
+    fn name(&self) -> &str {
+        "php:add"
+    }
+
+    fn directories(&self) -> Vec<PathBuf> {
+        self.0.clone()
+    }
+}
+
+/// Directories matching glob patterns, relative to a base directory.
+struct GlobDirectories {
+    name: &'static str,
+    base: PathBuf,
+    patterns: Vec<&'static str>,
+}
+
+impl DiscoverySource for GlobDirectories {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn directories(&self) -> Vec<PathBuf> {
+        let mut directories = Vec::new();
+
+        for pattern in self.patterns.iter() {
+            // The base directory may contain characters like "[", which must not be read as a pattern.
+            let pattern = PathBuf::from(Pattern::escape(self.base.to_str().unwrap())).join(pattern);
+            let mut matches: Vec<PathBuf> = match glob(pattern.to_str().unwrap()) {
+                Ok(paths) => paths.filter_map(Result::ok).filter(|path| path.is_dir()).collect(),
+                Err(e) => {
+                    debug!("Invalid discovery pattern \"{}\": {}", pattern.to_str().unwrap(), e);
+                    continue;
+                },
+            };
+
+            // Most recent versions first, so they have precedence when the same version is installed twice.
+            matches.sort_by_cached_key(|path| Reverse(natural_sort_key(path)));
+
+            directories.extend(matches);
+        }
+
+        directories
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum NaturalSortPart {
+    Text(String),
+    Number(u64),
+}
+
+/// Splits a path in text and numbers, so that "php8.10" is sorted after "php8.9".
+fn natural_sort_key(path: &Path) -> Vec<NaturalSortPart> {
+    let mut parts = Vec::new();
+    let mut chars = path.to_string_lossy().chars().collect::<Vec<char>>().into_iter().peekable();
+
+    while let Some(&c) = chars.peek() {
+        let is_digit = c.is_ascii_digit();
+        let mut part = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() == is_digit) {
+            part.push(c);
+        }
+
+        parts.push(match part.parse() {
+            Ok(number) if is_digit => NaturalSortPart::Number(number),
+            _ => NaturalSortPart::Text(part),
+        });
+    }
+
+    parts
+}
+
+/// Returns all sources of PHP binaries, in order of precedence.
+pub(crate) fn sources() -> Vec<Box<dyn DiscoverySource>> {
+    let mut sources: Vec<Box<dyn DiscoverySource>> = vec![
+        Box::new(EnvironmentVariable("RYMFONY_PATH")),
+        Box::new(ConfiguredPaths(global_config::load().discovery.paths.iter().map(PathBuf::from).collect())),
+    ];
+
+    // We don't want to use these PATH by default on MacOS "Big Sur",
+    // because Apple added a deprecation to the default binary which changes
+    // how the PHP version is displayed when running `php --version`.
+    if !is_macos_big_sur() {
+        sources.push(Box::new(EnvironmentVariable("PATH")));
+    }
+
+    let filesystem_root = if cfg!(target_family = "windows") { PathBuf::from("c:\\") } else { PathBuf::from("/") };
+
+    sources.extend(installation_sources(&filesystem_root, home_dir().as_deref(), !is_macos_big_sur()));
+
+    sources
+}
+
+/// Sources relative to the filesystem root and to the home directory, so they can be tested with a fake directory tree.
+fn installation_sources(
+    root: &Path,
+    home: Option<&Path>,
+    with_system_directories: bool,
+) -> Vec<Box<dyn DiscoverySource>> {
+    let mut sources: Vec<Box<dyn DiscoverySource>> = Vec::new();
+
+    let from_root = |name, patterns| Box::new(GlobDirectories { name, base: root.to_path_buf(), patterns });
+
+    if cfg!(target_family = "windows") {
+        sources.push(from_root("c:\\php", vec!["php"]));
+    } else if with_system_directories {
+        sources.push(from_root("system", vec!["usr/bin", "usr/sbin"]));
+    }
+
+    sources.push(from_root(
+        "Homebrew",
+        vec![
+            // Apple Silicon
+            "opt/homebrew/Cellar/php/*/bin",
+            "opt/homebrew/Cellar/php/*/sbin",
+            "opt/homebrew/Cellar/php@*/*/bin",
+            "opt/homebrew/Cellar/php@*/*/sbin",
+            // Intel
+            "usr/local/Cellar/php/*/bin",
+            "usr/local/Cellar/php/*/sbin",
+            "usr/local/Cellar/php@*/*/bin",
+            "usr/local/Cellar/php@*/*/sbin",
+        ],
+    ));
+    sources.push(from_root("Remi", vec!["opt/remi/php*/root/usr/bin", "opt/remi/php*/root/usr/sbin"]));
+    sources.push(from_root("local", vec!["usr/local/php*/bin"]));
+    sources.push(from_root(
+        "Nix",
+        vec![
+            "nix/var/nix/profiles/default/bin",
+            "nix/var/nix/profiles/default/sbin",
+            "run/current-system/sw/bin",
+        ],
+    ));
+
+    if let Some(home) = home {
+        let from_home = |name, patterns| Box::new(GlobDirectories { name, base: home.to_path_buf(), patterns });
+
+        sources.push(from_home("phpenv", vec![".phpenv/versions/*/bin", ".phpenv/versions/*/sbin"]));
+        sources.push(from_home("phpbrew", vec![".phpbrew/php/*/bin", ".phpbrew/php/*/sbin"]));
+        sources.push(from_home("asdf", vec![".asdf/installs/php/*/bin", ".asdf/installs/php/*/sbin"]));
+        sources.push(from_home("Nix", vec![".nix-profile/bin", ".nix-profile/sbin"]));
+    }
+
+    sources
+}
+
+fn is_macos_big_sur() -> bool {
+    if cfg!(not(target_os = "macos")) {
+        return false;
+    }
+
+    let os_infos = os_info::get();
+    let os_version = os_infos.version();
+    let bigsur_version = "11.0.0";
+
+    version_compare::compare_to(os_version.to_string(), bigsur_version, version_compare::Cmp::Gt).unwrap()
+}
+
+#[cfg(not(target_family = "windows"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::create_dir_all;
+
+    #[test]
+    fn sources_find_their_directories() {
+        let tree = env::temp_dir().join("rymfony_discovery_test");
+        let root = tree.join("root");
+        let home = tree.join("home");
+
+        let directories = [
+            root.join("usr/bin"),
+            root.join("opt/homebrew/Cellar/php/8.3.1/bin"),
+            root.join("opt/homebrew/Cellar/php@8.1/8.1.27/sbin"),
+            root.join("usr/local/Cellar/php@7.4/7.4.33/bin"),
+            root.join("opt/remi/php81/root/usr/bin"),
+            root.join("opt/remi/php82/root/usr/sbin"),
+            root.join("nix/var/nix/profiles/default/bin"),
+            home.join(".phpenv/versions/8.2.10/bin"),
+            home.join(".phpbrew/php/php-8.0.30/sbin"),
+            home.join(".asdf/installs/php/8.1.2/bin"),
+            home.join(".nix-profile/bin"),
+        ];
+        for directory in directories.iter() {
+            create_dir_all(directory).unwrap();
+        }
+
+        let found: Vec<(String, PathBuf)> = installation_sources(&root, Some(&home), true)
+            .iter()
+            .flat_map(|source| {
+                let name = source.name().to_string();
+                source.directories().into_iter().map(move |directory| (name.clone(), directory))
+            })
+            .collect();
+
+        std::fs::remove_dir_all(&tree).unwrap();
+
+        let expected = vec![
+            ("system", root.join("usr/bin")),
+            ("Homebrew", root.join("opt/homebrew/Cellar/php/8.3.1/bin")),
+            ("Homebrew", root.join("opt/homebrew/Cellar/php@8.1/8.1.27/sbin")),
+            ("Homebrew", root.join("usr/local/Cellar/php@7.4/7.4.33/bin")),
+            ("Remi", root.join("opt/remi/php81/root/usr/bin")),
+            ("Remi", root.join("opt/remi/php82/root/usr/sbin")),
+            ("Nix", root.join("nix/var/nix/profiles/default/bin")),
+            ("phpenv", home.join(".phpenv/versions/8.2.10/bin")),
+            ("phpbrew", home.join(".phpbrew/php/php-8.0.30/sbin")),
+            ("asdf", home.join(".asdf/installs/php/8.1.2/bin")),
+            ("Nix", home.join(".nix-profile/bin")),
+        ];
+
+        assert_eq!(
+            found,
+            expected.into_iter().map(|(name, directory)| (name.to_string(), directory)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn most_recent_versions_come_first() {
+        let tree = env::temp_dir().join("rymfony_discovery_order_test[1]");
+        for version in ["8.9.1", "8.10.0", "7.4.33", "8.10.12"].iter() {
+            create_dir_all(tree.join(".phpenv/versions").join(version)).unwrap();
+        }
+
+        let directories =
+            GlobDirectories { name: "phpenv", base: tree.clone(), patterns: vec![".phpenv/versions/*"] }.directories();
+
+        std::fs::remove_dir_all(&tree).unwrap();
+
+        assert_eq!(
+            directories,
+            ["8.10.12", "8.10.0", "8.9.1", "7.4.33"]
+                .iter()
+                .map(|version| tree.join(".phpenv/versions").join(version))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn environment_variable_directories() {
+        env::set_var("RYMFONY_DISCOVERY_TEST_PATH", "/opt/php/bin:/usr/local/bin");
+
+        let directories = EnvironmentVariable("RYMFONY_DISCOVERY_TEST_PATH").directories();
+
+        assert_eq!(directories, vec![PathBuf::from("/opt/php/bin"), PathBuf::from("/usr/local/bin")]);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::php::structs::PhpBinary;
+use std::env;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+#[cfg(not(target_family = "windows"))]
+use {crate::config::paths, std::fs::create_dir_all, std::fs::remove_file, std::os::unix::fs::symlink};
+
+/// Configures the environment shared by all PHP processes started by Rymfony,
+/// for the PHP server as well as for CLI commands.
+///
+/// The project's PHP CLI is put first in the PATH, so that scripts executing "php"
+/// (like Composer scripts or Symfony's Process component) use the same PHP version.
+pub(crate) fn apply(command: &mut Command, binary: &PhpBinary) {
+    let cli = binary.cli();
+
+    if cli.is_empty() {
+        return;
+    }
+
+    let cli_dir = match php_cli_directory(Path::new(cli)) {
+        Some(dir) => dir,
+        None => return,
+    };
+
+    let mut path_dirs = vec![cli_dir];
+    if let Some(path) = env::var_os("PATH") {
+        path_dirs.extend(env::split_paths(&path));
+    }
+
+    match env::join_paths(path_dirs) {
+        Ok(path) => {
+            command.env("PATH", path);
+        },
+        Err(e) => warn!("Could not add PHP to the PATH: {}", e),
+    }
+}
+
+#[cfg(not(target_family = "windows"))]
+fn php_cli_directory(cli: &Path) -> Option<PathBuf> {
+    // The CLI binary can be named like "php8.1", so a "php" symlink is created in the project's directory.
+    let shims_dir = paths::get_php_shims_dir();
+    let php_link = shims_dir.join("php");
+
+    let linked = create_dir_all(&shims_dir).and_then(|_| {
+        if php_link.symlink_metadata().is_ok() {
+            remove_file(&php_link)?;
+        }
+        symlink(cli, &php_link)
+    });
+
+    match linked {
+        Ok(_) => Some(shims_dir),
+        Err(e) => {
+            debug!("Could not create \"php\" link in {}: {}", shims_dir.to_str().unwrap(), e);
+            cli.parent().map(|dir| dir.to_path_buf())
+        },
+    }
+}
+
+#[cfg(target_family = "windows")]
+fn php_cli_directory(cli: &Path) -> Option<PathBuf> {
+    cli.parent().map(|dir| dir.to_path_buf())
+}
@@ -0,0 +1,220 @@
+use colored::*;
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Levels accepted by the "--level" option of the "logs" command, from the least to the most severe.
+pub(crate) const SEVERITY_NAMES: [&str; 6] = ["debug", "info", "deprecated", "notice", "warning", "error"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Severity {
+    Debug,
+    Info,
+    Deprecated,
+    Notice,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Converts labels like "PHP Fatal error" or PHP-FPM's "WARNING".
+    fn from_label(label: &str) -> Severity {
+        let label = label.trim_start_matches("PHP ").to_lowercase();
+
+        if label.contains("error") || label == "alert" {
+            Severity::Error
+        } else if label.contains("warning") {
+            Severity::Warning
+        } else if label.contains("notice") {
+            Severity::Notice
+        } else if label.contains("deprecated") || label.contains("strict") {
+            Severity::Deprecated
+        } else if label == "debug" {
+            Severity::Debug
+        } else {
+            Severity::Info
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match SEVERITY_NAMES.iter().position(|severity| severity.eq_ignore_ascii_case(name)) {
+            Some(0) => Ok(Severity::Debug),
+            Some(1) => Ok(Severity::Info),
+            Some(2) => Ok(Severity::Deprecated),
+            Some(3) => Ok(Severity::Notice),
+            Some(4) => Ok(Severity::Warning),
+            Some(5) => Ok(Severity::Error),
+            _ => Err(format!("Unknown log level \"{}\"", name)),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = SEVERITY_NAMES[*self as usize].to_uppercase();
+
+        // Padding is applied here, since it is lost once the name is colored.
+        write!(f, "{:<10}", name)
+    }
+}
+
+/// An entry of a PHP error log, with the lines that follow it, like a stack trace.
+#[derive(Debug, PartialEq)]
+pub(crate) struct ErrorLogRecord {
+    pub(crate) date: Option<String>,
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+    pub(crate) location: Option<String>,
+    pub(crate) trace: Vec<String>,
+}
+
+/// Groups the lines of PHP, PHP-FPM or PHP-CGI error logs into records, as they are read.
+pub(crate) struct ErrorLogParser {
+    worker_output_regex: Regex,
+    dated_line_regex: Regex,
+    label_regex: Regex,
+    location_regex: Regex,
+    record: Option<ErrorLogRecord>,
+}
+
+impl ErrorLogParser {
+    pub(crate) fn new() -> ErrorLogParser {
+        ErrorLogParser {
+            worker_output_regex: Regex::new(
+                r#"^\[([^\]]+)\] \w+: \[pool [^\]]+\] child \d+ said into std(?:err|out): "(.*?)"(?: \(truncated\.\.\.\))?$"#,
+            )
+            .unwrap(),
+            dated_line_regex: Regex::new(r"^\[([^\]]+)\] (.*)$").unwrap(),
+            label_regex: Regex::new(r"^(PHP [A-Za-z ]+|DEBUG|NOTICE|WARNING|ERROR|ALERT):\s+(.*)$").unwrap(),
+            location_regex: Regex::new(r"^(.*) in (\S+?)(?: on line |:)(\d+)$").unwrap(),
+            record: None,
+        }
+    }
+
+    /// Adds a line of the log. Returns the previous record when this line starts a new one.
+    pub(crate) fn push(&mut self, line: &str) -> Option<ErrorLogRecord> {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            return None;
+        }
+
+        // Lines written by PHP-FPM workers are unwrapped, so that PHP errors keep their own severity.
+        let (date, content, is_dated_line) = if let Some(captures) = self.worker_output_regex.captures(line) {
+            (Some(captures[1].to_string()), captures[2].to_string(), false)
+        } else if let Some(captures) = self.dated_line_regex.captures(line) {
+            (Some(captures[1].to_string()), captures[2].to_string(), true)
+        } else {
+            (None, line.to_string(), false)
+        };
+
+        // Other lines, like stack traces, continue the current record.
+        let (severity, message) = match (self.label_regex.captures(&content), self.record.as_mut()) {
+            (Some(captures), _) => (Severity::from_label(&captures[1]), format!("{}: {}", &captures[1], &captures[2])),
+            (None, Some(record)) if !is_dated_line => {
+                record.trace.push(content);
+                return None;
+            },
+            (None, _) => (Severity::Info, content),
+        };
+
+        let (message, location) = match self.location_regex.captures(&message) {
+            Some(captures) => (captures[1].to_string(), Some(format!("{}:{}", &captures[2], &captures[3]))),
+            None => (message, None),
+        };
+
+        self.record.replace(ErrorLogRecord { date, severity, message, location, trace: Vec::new() })
+    }
+
+    /// Returns the record being read, once no more lines are expected for it.
+    pub(crate) fn flush(&mut self) -> Option<ErrorLogRecord> {
+        self.record.take()
+    }
+}
+
+/// Renders a record as a single block colored by severity, with paths relative to the project directory.
+pub(crate) fn render(record: &ErrorLogRecord, project_dir: &Path) -> String {
+    let project_prefix = format!("{}{}", project_dir.display(), std::path::MAIN_SEPARATOR);
+    let relative = |text: &str| text.replace(&project_prefix, "");
+
+    let severity = record.severity.to_string();
+    let severity = match record.severity {
+        Severity::Error => severity.red().bold(),
+        Severity::Warning => severity.yellow().bold(),
+        Severity::Notice | Severity::Deprecated => severity.cyan(),
+        Severity::Info | Severity::Debug => severity.dimmed(),
+    };
+
+    let mut block = match &record.date {
+        Some(date) => format!("{} {} {}", format!("[{}]", date).dimmed(), severity, relative(&record.message)),
+        None => format!("{} {}", severity, relative(&record.message)),
+    };
+
+    if let Some(location) = &record.location {
+        block.push_str(&format!("\n    at {}", relative(location).bold()));
+    }
+    for line in record.trace.iter() {
+        block.push_str(&format!("\n    {}", relative(line).dimmed()));
+    }
+
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Vec<ErrorLogRecord> {
+        let mut parser = ErrorLogParser::new();
+        let mut records: Vec<ErrorLogRecord> = content.lines().filter_map(|line| parser.push(line)).collect();
+        records.extend(parser.flush());
+
+        records
+    }
+
+    #[test]
+    fn fatal_errors_are_grouped_with_their_stack_trace() {
+        let content = r##"[19-Oct-2026 10:00:00 UTC] PHP Warning:  Undefined variable $name in /app/src/Greeter.php on line 8
+[19-Oct-2026 10:00:01 UTC] PHP Fatal error:  Uncaught RuntimeException: Boom in /app/src/Kernel.php:12
+Stack trace:
+#0 /app/public/index.php(5): App\Kernel->handle()
+#1 {main}
+  thrown in /app/src/Kernel.php on line 12
+"##;
+
+        let records = parse(content);
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].severity, Severity::Warning);
+        assert_eq!(records[0].location.as_deref(), Some("/app/src/Greeter.php:8"));
+        assert_eq!(records[1].severity, Severity::Error);
+        assert_eq!(records[1].message, "PHP Fatal error: Uncaught RuntimeException: Boom");
+        assert_eq!(records[1].trace.len(), 4);
+
+        colored::control::set_override(false);
+        assert_eq!(
+            render(&records[0], Path::new("/app")),
+            "[19-Oct-2026 10:00:00 UTC] WARNING    PHP Warning: Undefined variable $name\n    at src/Greeter.php:8"
+        );
+    }
+
+    #[test]
+    fn fpm_worker_output_is_unwrapped() {
+        let content = r##"[19-Oct-2026 10:00:00] NOTICE: fpm is running, pid 42
+[19-Oct-2026 10:00:02] WARNING: [pool www] child 43 said into stderr: "PHP Deprecated:  strlen(): Passing null is deprecated in /app/src/Text.php on line 3"
+[19-Oct-2026 10:00:03] WARNING: [pool www] child 43 said into stderr: "PHP Fatal error:  Uncaught Error: Oops in /app/src/Text.php:9"
+[19-Oct-2026 10:00:03] WARNING: [pool www] child 43 said into stderr: "Stack trace:"
+[19-Oct-2026 10:00:03] WARNING: [pool www] child 43 said into stderr: "#0 {main}""##;
+
+        let records = parse(content);
+
+        let severities: Vec<Severity> = records.iter().map(|record| record.severity).collect();
+        assert_eq!(severities, vec![Severity::Notice, Severity::Deprecated, Severity::Error]);
+        assert_eq!(records[2].trace, vec!["Stack trace:", "#0 {main}"]);
+        assert!(records.iter().filter(|record| record.severity >= "warning".parse().unwrap()).count() == 1);
+    }
+}
@@ -0,0 +1,306 @@
+use crate::config::global_config;
+use crate::config::paths;
+use crate::config::project_config;
+use crate::config::project_config::FpmConfig;
+use crate::config::project_config::FpmProcessManager;
+use regex::RegexBuilder;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use sysinfo::CpuRefreshKind;
+use sysinfo::RefreshKind;
+use sysinfo::System;
+use sysinfo::SystemExt;
+
+const POOL_SETTINGS_START: &str =
+    "; Process manager settings, managed by Rymfony: use the \"[fpm]\" table of \".rymfony.toml\" to change them.";
+const POOL_SETTINGS_END: &str = "; End of process manager settings";
+
+/// Pool directives written in the managed block. They are commented out anywhere else in the config.
+const MANAGED_DIRECTIVES: [&str; 9] = [
+    "pm",
+    "pm.max_children",
+    "pm.start_servers",
+    "pm.min_spare_servers",
+    "pm.max_spare_servers",
+    "pm.max_requests",
+    "request_terminate_timeout",
+    "request_slowlog_timeout",
+    "slowlog",
+];
+
+const DEFAULT_MAX_CHILDREN: u32 = 5;
+
+// Rough memory usage of a PHP-FPM worker running a framework in development mode.
+const AUTO_WORKER_MEMORY: u64 = 64 * 1024 * 1024;
+const AUTO_WORKERS_PER_CPU: u64 = 4;
+const AUTO_MIN_CHILDREN: u32 = 4;
+const AUTO_MAX_CHILDREN: u32 = 64;
+
+// Set by "serve --slow-threshold", 0 meaning the configuration is used.
+static SLOW_THRESHOLD: AtomicU32 = AtomicU32::new(0);
+
+/// Process manager settings of the "www" pool, once defaults and automatic sizing are applied.
+#[derive(Debug, PartialEq)]
+pub(crate) struct PoolSettings {
+    pub(crate) pm: &'static str,
+    pub(crate) max_children: u32,
+    pub(crate) start_servers: u32,
+    pub(crate) min_spare_servers: u32,
+    pub(crate) max_spare_servers: u32,
+    pub(crate) max_requests: Option<u32>,
+    pub(crate) request_terminate_timeout: Option<String>,
+    pub(crate) request_slowlog_timeout: Option<String>,
+    pub(crate) slowlog: Option<PathBuf>,
+}
+
+/// Merges the "[fpm]" tables of the project and global configurations.
+pub(crate) fn get_pool_settings() -> PoolSettings {
+    let mut config = project_config::load().fpm.or(global_config::load().fpm);
+
+    let slow_threshold = SLOW_THRESHOLD.load(Ordering::Relaxed);
+    if slow_threshold > 0 {
+        config.request_slowlog_timeout = Some(toml::Value::Integer(slow_threshold.into()));
+    }
+
+    let mut settings = resolve(config, get_auto_max_children);
+    if settings.request_slowlog_timeout.is_some() {
+        settings.slowlog = Some(paths::get_php_slow_log_file());
+    }
+
+    debug!("PHP-FPM pool uses \"pm = {}\" with up to {} children.", settings.pm, settings.max_children);
+
+    settings
+}
+
+/// Overrides the slow log timeout of the configuration, in seconds.
+pub(crate) fn set_slow_threshold(seconds: u32) {
+    SLOW_THRESHOLD.store(seconds, Ordering::Relaxed);
+}
+
+fn resolve(config: FpmConfig, auto_max_children: impl FnOnce() -> u32) -> PoolSettings {
+    let process_manager = config.pm.unwrap_or(FpmProcessManager::Dynamic);
+
+    let max_children = match (config.max_children, process_manager) {
+        (Some(max_children), _) => max_children.max(1),
+        (None, FpmProcessManager::Auto) => auto_max_children(),
+        (None, _) => DEFAULT_MAX_CHILDREN,
+    };
+
+    // Spare servers that are not configured are derived from the number of children,
+    // which gives the historical 2, 1 and 3 values for 5 children,
+    // and are kept between the configured ones so that PHP-FPM accepts the pool.
+    let mut min_spare_servers = config.min_spare_servers.unwrap_or_else(|| (max_children / 8).max(1));
+    let start_servers = config.start_servers.unwrap_or_else(|| {
+        (max_children / 4)
+            .max(2)
+            .max(min_spare_servers)
+            .min(config.max_spare_servers.unwrap_or(max_children))
+            .min(max_children)
+    });
+    let max_spare_servers =
+        config.max_spare_servers.unwrap_or_else(|| (max_children / 2).max(start_servers + 1).min(max_children));
+    if config.min_spare_servers.is_none() {
+        min_spare_servers = min_spare_servers.min(start_servers);
+    }
+
+    let pm = match process_manager {
+        FpmProcessManager::Dynamic | FpmProcessManager::Auto => "dynamic",
+        FpmProcessManager::Static => "static",
+        FpmProcessManager::Ondemand => "ondemand",
+    };
+
+    let request_terminate_timeout =
+        config.request_terminate_timeout.and_then(|timeout| resolve_duration("request_terminate_timeout", timeout));
+    let request_slowlog_timeout =
+        config.request_slowlog_timeout.and_then(|timeout| resolve_duration("request_slowlog_timeout", timeout));
+
+    let settings = PoolSettings {
+        pm,
+        max_children,
+        start_servers,
+        min_spare_servers,
+        max_spare_servers,
+        max_requests: config.max_requests,
+        request_terminate_timeout,
+        request_slowlog_timeout,
+        slowlog: None,
+    };
+
+    if settings.pm == "dynamic" && !settings.has_valid_spare_servers() {
+        warn!(
+            "PHP-FPM may refuse to start: the \"[fpm]\" settings must follow min_spare_servers ({}) <= start_servers ({}) <= max_spare_servers ({}) <= max_children ({}).",
+            settings.min_spare_servers, settings.start_servers, settings.max_spare_servers, settings.max_children
+        );
+    }
+
+    settings
+}
+
+impl PoolSettings {
+    fn has_valid_spare_servers(&self) -> bool {
+        1 <= self.min_spare_servers
+            && self.min_spare_servers <= self.start_servers
+            && self.start_servers <= self.max_spare_servers
+            && self.max_spare_servers <= self.max_children
+    }
+}
+
+fn resolve_duration(name: &str, value: toml::Value) -> Option<String> {
+    match value {
+        toml::Value::Integer(seconds) => Some(seconds.to_string()),
+        toml::Value::String(duration) => Some(duration),
+        _ => {
+            warn!("Ignoring \"{}\" FPM setting: it must be a number of seconds or a string.", name);
+            None
+        },
+    }
+}
+
+/// Allows a few workers per CPU, as long as they fit in the available memory.
+fn get_auto_max_children() -> u32 {
+    let system = System::new_with_specifics(RefreshKind::new().with_memory().with_cpu(CpuRefreshKind::new()));
+
+    let by_cpu = system.cpus().len().max(1) as u64 * AUTO_WORKERS_PER_CPU;
+    let by_memory = system.available_memory() / AUTO_WORKER_MEMORY;
+
+    let max_children = (by_cpu.min(by_memory) as u32).clamp(AUTO_MIN_CHILDREN, AUTO_MAX_CHILDREN);
+
+    info!(
+        "PHP-FPM pool sized automatically to {} children ({} CPUs, {} MB of available memory).",
+        max_children,
+        system.cpus().len(),
+        system.available_memory() / 1024 / 1024
+    );
+
+    max_children
+}
+
+/// Replaces the block of process manager settings in the FPM config,
+/// and comments out the same directives elsewhere, like in configs generated by older versions.
+pub(crate) fn apply_pool_settings(original_content: &str, settings: &PoolSettings) -> String {
+    let mut content = original_content.to_string();
+    remove_managed_block(&mut content, POOL_SETTINGS_START, POOL_SETTINGS_END);
+
+    let directives = MANAGED_DIRECTIVES.iter().map(|directive| regex::escape(directive)).collect::<Vec<_>>().join("|");
+    let directives_regex =
+        RegexBuilder::new(&format!(r"^([ \t]*(?:{})[ \t]*=)", directives)).multi_line(true).build().unwrap();
+    let content = directives_regex.replace_all(&content, ";$1");
+
+    let mut content = format!("{}\n\n{}\n", content.trim_end(), POOL_SETTINGS_START);
+
+    content.push_str(&format!("pm = {}\n", settings.pm));
+    content.push_str(&format!("pm.max_children = {}\n", settings.max_children));
+
+    if settings.pm == "dynamic" {
+        content.push_str(&format!("pm.start_servers = {}\n", settings.start_servers));
+        content.push_str(&format!("pm.min_spare_servers = {}\n", settings.min_spare_servers));
+        content.push_str(&format!("pm.max_spare_servers = {}\n", settings.max_spare_servers));
+    }
+    if let Some(max_requests) = settings.max_requests {
+        content.push_str(&format!("pm.max_requests = {}\n", max_requests));
+    }
+    if let Some(request_terminate_timeout) = &settings.request_terminate_timeout {
+        content.push_str(&format!("request_terminate_timeout = {}\n", request_terminate_timeout));
+    }
+    if let (Some(request_slowlog_timeout), Some(slowlog)) = (&settings.request_slowlog_timeout, &settings.slowlog) {
+        content.push_str(&format!("request_slowlog_timeout = {}\n", request_slowlog_timeout));
+        content.push_str(&format!("slowlog = {}\n", slowlog.to_str().unwrap()));
+    }
+
+    content.push_str(POOL_SETTINGS_END);
+    content.push('\n');
+
+    content
+}
+
+/// Removes a block of the config written by Rymfony, with the blank lines in front of it,
+/// so that writing it again at the end of the config always gives the same content.
+pub(crate) fn remove_managed_block(content: &mut String, start_marker: &str, end_marker: &str) {
+    if let Some(start) = content.find(start_marker) {
+        let end = content[start..].find(end_marker).map(|end| start + end + end_marker.len()).unwrap_or(content.len());
+        let start = content[..start].trim_end().len();
+        content.replace_range(start..end, "");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spare_servers_are_derived_from_children() {
+        let settings = resolve(FpmConfig::default(), || unreachable!());
+        assert_eq!(
+            (
+                settings.pm,
+                settings.max_children,
+                settings.start_servers,
+                settings.min_spare_servers,
+                settings.max_spare_servers
+            ),
+            ("dynamic", 5, 2, 1, 3)
+        );
+
+        let config = FpmConfig { pm: Some(FpmProcessManager::Auto), ..FpmConfig::default() };
+        let settings = resolve(config, || 32);
+        assert_eq!(
+            (settings.max_children, settings.start_servers, settings.min_spare_servers, settings.max_spare_servers),
+            (32, 8, 4, 16)
+        );
+
+        let config = FpmConfig { max_children: Some(2), start_servers: Some(1), ..FpmConfig::default() };
+        let settings = resolve(config, || unreachable!());
+        assert_eq!((settings.start_servers, settings.min_spare_servers, settings.max_spare_servers), (1, 1, 2));
+    }
+
+    #[test]
+    fn derived_spare_servers_fit_the_configured_ones() {
+        let config = FpmConfig { max_children: Some(20), min_spare_servers: Some(10), ..FpmConfig::default() };
+        let settings = resolve(config, || unreachable!());
+        assert_eq!((settings.start_servers, settings.min_spare_servers, settings.max_spare_servers), (10, 10, 11));
+        assert!(settings.has_valid_spare_servers());
+
+        let config = FpmConfig { max_children: Some(20), max_spare_servers: Some(1), ..FpmConfig::default() };
+        let settings = resolve(config, || unreachable!());
+        assert_eq!((settings.start_servers, settings.min_spare_servers, settings.max_spare_servers), (1, 1, 1));
+
+        for max_children in 1..=64 {
+            let config = FpmConfig { max_children: Some(max_children), ..FpmConfig::default() };
+            assert!(resolve(config, || unreachable!()).has_valid_spare_servers());
+        }
+
+        let config = FpmConfig {
+            max_children: Some(20),
+            start_servers: Some(5),
+            min_spare_servers: Some(10),
+            ..FpmConfig::default()
+        };
+        assert!(!resolve(config, || unreachable!()).has_valid_spare_servers());
+    }
+
+    #[test]
+    fn pool_settings_replace_hard_coded_ones() {
+        let config = FpmConfig {
+            pm: Some(FpmProcessManager::Static),
+            max_children: Some(20),
+            request_terminate_timeout: Some(toml::Value::Integer(60)),
+            request_slowlog_timeout: Some(toml::Value::String(String::from("5s"))),
+            ..FpmConfig::default()
+        };
+        let mut settings = resolve(config, || unreachable!());
+        settings.slowlog = Some(PathBuf::from("/tmp/slow.log"));
+
+        let legacy = "[www]\npm = dynamic\npm.max_children = 5\npm.status_path = /_fpm-status\n";
+        let content = apply_pool_settings(legacy, &settings);
+
+        assert_eq!(
+            content,
+            format!(
+                "[www]\n;pm = dynamic\n;pm.max_children = 5\npm.status_path = /_fpm-status\n\n{}\npm = static\npm.max_children = 20\nrequest_terminate_timeout = 60\nrequest_slowlog_timeout = 5s\nslowlog = /tmp/slow.log\n{}\n",
+                POOL_SETTINGS_START, POOL_SETTINGS_END
+            )
+        );
+        assert_eq!(apply_pool_settings(&content, &settings), content);
+    }
+}
@@ -0,0 +1,113 @@
+use crate::config::project_config;
+use crate::config::project_config::PROJECT_CONFIG_FILE;
+use crate::utils::project_directory::get_project_root;
+use std::collections::BTreeMap;
+use std::fs::read_to_string;
+
+/// INI file at the root of the project, with settings applied to the PHP server.
+pub(crate) const PROJECT_INI_FILE: &str = "php.ini";
+
+/// An INI setting of the project, overriding the one from PHP's own configuration.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct IniSetting {
+    pub(crate) name: String,
+    pub(crate) value: String,
+    pub(crate) source: &'static str,
+}
+
+/// Returns the INI settings of the project, sorted by name.
+/// Settings from the "[php_ini]" section of the project config override the ones from the project's "php.ini" file.
+pub(crate) fn get_project_settings() -> Vec<IniSetting> {
+    let mut settings: BTreeMap<String, IniSetting> = BTreeMap::new();
+
+    let ini_file_path = get_project_root().join(PROJECT_INI_FILE);
+    if ini_file_path.is_file() {
+        match read_to_string(&ini_file_path) {
+            Ok(content) => {
+                for (name, value) in parse_ini(&content) {
+                    settings.insert(name.clone(), IniSetting { name, value, source: PROJECT_INI_FILE });
+                }
+            },
+            Err(e) => warn!("Could not read \"{}\": {}", PROJECT_INI_FILE, e),
+        }
+    }
+
+    for (name, value) in project_config::load().php_ini {
+        match to_ini_value(&value) {
+            Some(value) => {
+                settings.insert(name.clone(), IniSetting { name, value, source: PROJECT_CONFIG_FILE });
+            },
+            None => {
+                warn!("Ignoring \"{}\" PHP setting from \"{}\": unsupported value type.", name, PROJECT_CONFIG_FILE)
+            },
+        }
+    }
+
+    settings.into_values().collect()
+}
+
+/// Reads the "name = value" entries of an INI file. Sections are ignored, since PHP applies them to specific hosts or paths.
+fn parse_ini(content: &str) -> Vec<(String, String)> {
+    let mut entries = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with(';') || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        }
+
+        let (name, value) = match line.split_once('=') {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        let value = value.trim();
+        let value = if let Some(quoted) = value.strip_prefix('"') {
+            quoted.split('"').next().unwrap_or("")
+        } else {
+            value.split(';').next().unwrap_or("").trim()
+        };
+
+        entries.push((name.trim().to_string(), value.to_string()));
+    }
+
+    entries
+}
+
+fn to_ini_value(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(value) => Some(value.clone()),
+        toml::Value::Integer(value) => Some(value.to_string()),
+        toml::Value::Float(value) => Some(value.to_string()),
+        toml::Value::Boolean(value) => Some(String::from(if *value { "On" } else { "Off" })),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_ini_entries() {
+        let content = "
+; Comment
+[PHP]
+memory_limit = 512M ; inline comment
+error_reporting = E_ALL & ~E_DEPRECATED
+date.timezone = \"Europe/Paris\"
+# Another comment
+display_errors=On
+";
+
+        assert_eq!(
+            parse_ini(content),
+            vec![
+                (String::from("memory_limit"), String::from("512M")),
+                (String::from("error_reporting"), String::from("E_ALL & ~E_DEPRECATED")),
+                (String::from("date.timezone"), String::from("Europe/Paris")),
+                (String::from("display_errors"), String::from("On")),
+            ]
+        );
+    }
+}
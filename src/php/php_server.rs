@@ -1,82 +1,358 @@
+use std::fs::read_to_string;
 use std::fs::write;
 
 use crate::php::binaries;
+use crate::php::environment;
+use crate::php::server_builtin;
+use crate::php::server_builtin::get_start_command as get_builtin_start_command;
 use crate::php::server_cgi::get_start_command as get_cgi_start_command;
+use crate::php::server_cgi::get_workers_count as get_cgi_workers_count;
+#[cfg(not(target_family = "windows"))]
+use crate::php::server_fpm;
 use crate::php::server_fpm::get_start_command as get_fpm_start_command;
+use crate::php::structs::PhpBinary;
 use crate::php::structs::PhpServerSapi;
+use crate::php::xdebug;
 
+use crate::config::paths;
 use crate::config::paths::php_server_pid_file;
+use crate::php::backend::BackendAddress;
+use crate::php::backend::BackendError;
+use crate::php::backend::BackendProtocol;
+use crate::php::backend::PhpBackend;
 use crate::utils::network::find_available_port;
+use crate::utils::stop_process;
 use is_executable::IsExecutable;
+use std::error::Error;
+use std::io;
 use std::path::PathBuf;
 use std::process::Child;
 use std::process::Command;
-use std::thread;
 use std::time;
 
 const PHP_DEFAULT_PORT: u16 = 60000;
 
+// Avoids restarting PHP in a loop when it exits right after starting.
+const RESTART_DELAY: time::Duration = time::Duration::from_millis(1000);
+
 #[derive(Clone)]
 pub(crate) struct PhpServerStartInput {
     pub(crate) sapi: PhpServerSapi,
-    pub(crate) port: u16,
+    /// PHP-FPM listens to a single port, and each PHP-CGI worker listens to its own port.
+    pub(crate) ports: Vec<u16>,
     pub(crate) php_bin: String,
+    pub(crate) binary: PhpBinary,
+    /// Used by PHP's built-in web server, which serves files on its own.
+    pub(crate) document_root: String,
+}
+
+/// A process of the PHP server, restarted on its own when it exits.
+struct PhpServerProcess {
+    port: u16,
+    command: Command,
+    process: Child,
+    exited_at: Option<time::Instant>,
+}
+
+impl PhpServerProcess {
+    fn start(input: &PhpServerStartInput, port: u16) -> Result<PhpServerProcess, Box<dyn Error>> {
+        let mut command = get_php_server_command(input, port)?;
+        let process = start_php_server(&mut command, port);
+
+        Ok(PhpServerProcess { port, command, process, exited_at: None })
+    }
+
+    /// Restarts the process a moment after it exited. Returns whether it was restarted.
+    fn check(&mut self, input: &PhpServerStartInput) -> io::Result<bool> {
+        match self.exited_at {
+            None => {
+                if let Some(status) = self.process.try_wait()? {
+                    debug!(
+                        "PHP on port {} stopped with exit code {}. Restarting it.",
+                        self.port,
+                        status.code().unwrap_or(255)
+                    );
+                    self.exited_at = Some(time::Instant::now());
+                }
+
+                return Ok(false);
+            },
+            Some(exited_at) if exited_at.elapsed() < RESTART_DELAY => return Ok(false),
+            Some(_) => (),
+        }
+
+        match get_php_server_command(input, self.port) {
+            Ok(command) => self.command = command,
+            Err(e) => warn!("Restarting PHP with its previous configuration: {}", e),
+        }
+
+        self.exited_at = None;
+        self.process = start_php_server(&mut self.command, self.port);
+        debug!("PHP restarted, running with PID {}", self.process.id());
+
+        Ok(true)
+    }
+
+    /// Starts the process again right away, with a new command.
+    /// Nothing is changed if the command cannot be created, like when the config is invalid.
+    fn restart(&mut self, input: &PhpServerStartInput) -> Result<(), Box<dyn Error>> {
+        let command = get_php_server_command(input, self.port)?;
+
+        self.stop();
+        self.command = command;
+        self.exited_at = None;
+        self.process = start_php_server(&mut self.command, self.port);
+        debug!("PHP restarted, running with PID {}", self.process.id());
+
+        Ok(())
+    }
+
+    /// PHP-FPM reloads on its own, with the config that has just been written again.
+    #[cfg(not(target_family = "windows"))]
+    fn reload_fpm(&mut self, input: &PhpServerStartInput) -> Result<(), Box<dyn Error>> {
+        self.command = get_php_server_command(input, self.port)?;
+
+        // An exited process restarts with the new command anyway.
+        if self.exited_at.is_none() {
+            server_fpm::reload(self.process.id())?;
+        }
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        if self.exited_at.is_none() {
+            stop_process::stop(&self.process.id().to_string());
+        }
+        let _ = self.process.wait();
+    }
+}
+
+/// PHP server processes started and restarted by Rymfony:
+/// PHP-FPM, a pool of PHP-CGI workers, or PHP's built-in web server.
+pub(crate) struct ManagedBackend {
+    input: PhpServerStartInput,
+    processes: Vec<PhpServerProcess>,
 }
 
-pub(crate) fn get_php_server_start_input() -> (Command, PhpServerStartInput) {
-    let php_bin = binaries::get_project_version();
+impl ManagedBackend {
+    pub(crate) fn new(input: PhpServerStartInput) -> ManagedBackend {
+        ManagedBackend { input, processes: Vec::new() }
+    }
+}
+
+impl PhpBackend for ManagedBackend {
+    fn name(&self) -> String {
+        match self.input.sapi {
+            PhpServerSapi::CLI => String::from("PHP built-in"),
+            sapi => format!("PHP-{}", sapi),
+        }
+    }
+
+    fn protocol(&self) -> BackendProtocol {
+        match self.input.sapi {
+            PhpServerSapi::CLI => BackendProtocol::Http,
+            _ => BackendProtocol::FastCgi,
+        }
+    }
+
+    fn addresses(&self) -> Vec<BackendAddress> {
+        self.input.ports.iter().map(|port| BackendAddress::local(*port)).collect()
+    }
+
+    fn log_files(&self) -> Vec<PathBuf> {
+        match self.input.sapi {
+            PhpServerSapi::FPM => vec![paths::get_php_server_error_file(), paths::get_php_process_err_file()],
+            PhpServerSapi::CGI => vec![paths::get_php_cgi_log_file()],
+            PhpServerSapi::CLI => vec![paths::get_php_process_err_file()],
+            PhpServerSapi::Unknown => Vec::new(),
+        }
+    }
+
+    fn start(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.input.ports.len() > 1 {
+            info!("Starting {} {} workers", self.input.ports.len(), self.name());
+        }
+
+        for port in self.input.ports.iter() {
+            match PhpServerProcess::start(&self.input, *port) {
+                Ok(process) => self.processes.push(process),
+                Err(e) => {
+                    self.stop();
+                    return Err(e);
+                },
+            }
+        }
+
+        write_php_server_pids(&self.processes);
+
+        Ok(())
+    }
+
+    /// Each process is restarted on its own.
+    fn check_health(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut restarted = false;
+        for process in self.processes.iter_mut() {
+            restarted |= process.check(&self.input)?;
+        }
+
+        if restarted {
+            write_php_server_pids(&self.processes);
+        }
+
+        Ok(())
+    }
+
+    /// PHP-FPM replaces its workers gracefully, and other processes are restarted one after the other,
+    /// so that the remaining PHP-CGI workers keep handling requests.
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        #[cfg(not(target_family = "windows"))]
+        if self.input.sapi == PhpServerSapi::FPM {
+            for process in self.processes.iter_mut() {
+                process.reload_fpm(&self.input)?;
+            }
+
+            return Ok(());
+        }
+
+        let input = &self.input;
+        let result = self.processes.iter_mut().try_for_each(|process| process.restart(input));
+
+        write_php_server_pids(&self.processes);
+
+        result
+    }
+
+    fn stop(&mut self) {
+        for process in self.processes.iter_mut() {
+            process.stop();
+        }
+        self.processes.clear();
+    }
+}
+
+/// Selects the PHP binary of the project, and the SAPI running the server,
+/// which is the best available one unless a SAPI is requested.
+pub(crate) fn get_php_server_start_input(
+    document_root: &str,
+    php_entrypoint_file: &str,
+    requested_sapi: Option<PhpServerSapi>,
+) -> Result<PhpServerStartInput, BackendError> {
+    let binary = match binaries::get_project_binary() {
+        Some(selected) => selected,
+        None => {
+            error!("You can execute \"rymfony php:list --refresh\" to update binaries paths cache.");
+            return Err(BackendError(String::from("No PHP binary found for this project.")));
+        },
+    };
+
+    let sapi = match (requested_sapi, binary.server_sapi()) {
+        (Some(PhpServerSapi::FPM), _) if cfg!(target_family = "windows") => {
+            return Err(BackendError(String::from("PHP-FPM is not supported on Windows.")));
+        },
+        (Some(sapi), _) if binary.has_sapi(&sapi) => sapi,
+        (Some(sapi), _) => {
+            return Err(BackendError(format!("The {} SAPI was not found for PHP {}.", sapi, binary)));
+        },
+        (None, Some(sapi)) => sapi,
+        (None, None) => {
+            return Err(BackendError(String::from(
+                "Rymfony only supports PHP-FPM (linux), PHP-CGI and the PHP CLI, and none of these SAPIs was found.",
+            )));
+        },
+    };
+
+    let php_bin = binary.sapi_path(&sapi);
 
     let phpbin_path = PathBuf::from(php_bin.as_str());
 
     if !phpbin_path.is_executable() {
-        error!("PHP binary not found or not executable: {}", php_bin.as_str());
         error!("You can execute \"rymfony php:list --refresh\" to update binaries paths cache.");
-        panic!("Unable to start the required PHP binary");
+        return Err(BackendError(format!("PHP binary not found or not executable: {}", php_bin)));
     }
 
-    let php_server_port = find_available_port(PHP_DEFAULT_PORT);
+    let processes_count = match sapi {
+        PhpServerSapi::CGI => get_cgi_workers_count(),
+        _ => 1,
+    };
 
-    let (php_server_sapi, command) = get_php_server_start_command(&php_bin, &php_server_port);
+    if sapi == PhpServerSapi::CLI {
+        if requested_sapi.is_none() {
+            warn!("Neither PHP-FPM nor PHP-CGI was found for PHP {}, using PHP's built-in web server.", binary);
+        }
+        warn!("It handles a single request at a time, so slow or concurrent requests will wait for each other.");
+        server_builtin::write_router_script(php_entrypoint_file);
+    }
+
+    let mut ports: Vec<u16> = Vec::new();
+    for _ in 0..processes_count {
+        let start_from_port = ports.last().map_or(PHP_DEFAULT_PORT, |port| port + 1);
+        ports.push(find_available_port(start_from_port));
+    }
 
-    (command, PhpServerStartInput { sapi: php_server_sapi, port: php_server_port, php_bin: String::from(php_bin) })
+    Ok(PhpServerStartInput { sapi, ports, php_bin, binary, document_root: document_root.to_string() })
 }
 
-fn get_php_server_start_command(php_bin: &String, port: &u16) -> (PhpServerSapi, Command) {
-    let (sapi, command) = if php_bin.contains("-fpm") && cfg!(not(target_family = "windows")) {
-        get_fpm_start_command(php_bin.clone(), port)
-    } else if php_bin.contains("-cgi") {
-        get_cgi_start_command(php_bin.clone(), port)
-    } else {
-        panic!("Rymfony only supports PHP-FPM (linux) and PHP-CGI (Windows), and none of these SAPIs was found.");
+/// Creates the command starting the PHP server on a port.
+/// It is created again each time the server starts, so that changes to the project's PHP settings are applied.
+pub(crate) fn get_php_server_command(input: &PhpServerStartInput, port: u16) -> Result<Command, Box<dyn Error>> {
+    let (_, mut command) = match input.sapi {
+        PhpServerSapi::FPM => get_fpm_start_command(input.php_bin.clone(), &port),
+        PhpServerSapi::CLI => get_builtin_start_command(input.php_bin.clone()),
+        _ => get_cgi_start_command(input.php_bin.clone(), &port),
     };
 
-    (sapi, command)
-}
+    // The FPM config has just been written, so it can be checked before starting the server.
+    #[cfg(not(target_family = "windows"))]
+    if input.sapi == PhpServerSapi::FPM {
+        server_fpm::test_config(&input.php_bin)?;
+    }
+
+    environment::apply(&mut command, &input.binary);
+
+    if let Some(mode) = xdebug::get_mode() {
+        command.args(xdebug::get_server_arguments(&input.binary, &input.php_bin, &mode)?);
+    }
+
+    if input.sapi == PhpServerSapi::CLI {
+        server_builtin::add_server_arguments(&mut command, &port, &input.document_root);
+    }
 
-pub(crate) fn start_php_server(command: &mut Command, input: PhpServerStartInput) -> Child {
-    let sleep_time = time::Duration::from_millis(1000);
-    thread::sleep(sleep_time);
+    Ok(command)
+}
 
+fn start_php_server(command: &mut Command, port: u16) -> Child {
     let mut process = command.spawn().expect("Could not start PHP server.");
     let process_status = process.try_wait();
 
     match process_status {
         Ok(Some(status)) => panic!("PHP server exited with {}", status),
         Ok(None) => {
-            info!("PHP server is ready and listening to port {}", &input.port);
+            info!("PHP server is ready and listening to port {}", port);
         },
         Err(e) => panic!("An error occured when checking PHP server health: {:?}", e),
     }
 
-    let process_pid = process.id();
-
-    let process_pid_string = if process_pid > 0 {
-        process_pid.to_string()
-    } else {
+    if process.id() == 0 {
         panic!("Could not retrieve PHP server's PID. Maybe the server has failed to start, or stopped right after starting.");
-    };
-
-    write(php_server_pid_file(), &process_pid_string).expect("Could not write PHP server PID to file.");
+    }
 
     process
 }
+
+/// Writes the PID of all PHP server processes, one per line.
+fn write_php_server_pids(processes: &[PhpServerProcess]) {
+    let pids: Vec<String> = processes.iter().map(|process| process.process.id().to_string()).collect();
+
+    write(php_server_pid_file(), pids.join("\n")).expect("Could not write PHP server PID to file.");
+}
+
+/// Returns the PID of all PHP server processes.
+pub(crate) fn get_php_server_pids() -> Vec<String> {
+    read_to_string(php_server_pid_file())
+        .unwrap_or_default()
+        .lines()
+        .map(|pid| pid.trim().to_string())
+        .filter(|pid| !pid.is_empty())
+        .collect()
+}
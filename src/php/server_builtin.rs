@@ -0,0 +1,64 @@
+use crate::config::paths;
+use crate::php::ini;
+use crate::php::structs::PhpServerSapi;
+use std::fs::read_to_string;
+use std::fs::write;
+use std::fs::OpenOptions;
+use std::process::Command;
+use std::process::Stdio;
+
+// The placeholders between brackets {{ }} will be replaced with proper values.
+const ROUTER_SCRIPT: &str = r#"<?php
+
+// This file is generated by Rymfony, any change will be lost.
+// Existing files are served by PHP's built-in server, and other requests are passed to the entrypoint.
+
+$path = parse_url($_SERVER['REQUEST_URI'], PHP_URL_PATH);
+
+if (is_string($path) && $path !== '/' && is_file($_SERVER['DOCUMENT_ROOT'].$path)) {
+    return false;
+}
+
+$_SERVER['SCRIPT_FILENAME'] = $_SERVER['DOCUMENT_ROOT'].DIRECTORY_SEPARATOR.'{{ php_entrypoint_file }}';
+$_SERVER['SCRIPT_NAME'] = '/{{ php_entrypoint_file }}';
+$_SERVER['PHP_SELF'] = '/{{ php_entrypoint_file }}';
+$_SERVER['SERVER_SOFTWARE'] = 'Rymfony/PHP';
+
+chdir(dirname($_SERVER['SCRIPT_FILENAME']));
+
+require $_SERVER['SCRIPT_FILENAME'];
+"#;
+
+/// Creates the command running PHP's built-in web server.
+/// The server arguments are added afterwards by "add_server_arguments",
+/// because PHP ignores the options that follow the router script.
+pub(crate) fn get_start_command(php_bin: String) -> (PhpServerSapi, Command) {
+    let mut file_options = OpenOptions::new();
+    file_options.read(true).append(true).write(true).create(true);
+
+    let log_file = file_options.open(paths::get_php_process_log_file()).unwrap();
+    let err_file = file_options.open(paths::get_php_process_err_file()).unwrap();
+
+    let mut command = Command::new(php_bin);
+    command.stdout(Stdio::from(log_file)).stderr(Stdio::from(err_file));
+
+    for setting in ini::get_project_settings() {
+        command.arg("-d").arg(format!("{}={}", setting.name, setting.value));
+    }
+
+    (PhpServerSapi::CLI, command)
+}
+
+pub(crate) fn add_server_arguments(command: &mut Command, port: &u16, document_root: &str) {
+    command.arg("-S").arg(format!("127.0.0.1:{}", port)).arg("-t").arg(document_root).arg(paths::get_php_router_file());
+}
+
+pub(crate) fn write_router_script(php_entrypoint_file: &str) {
+    let router_file = paths::get_php_router_file();
+    let content = ROUTER_SCRIPT.replace("{{ php_entrypoint_file }}", &php_entrypoint_file.replace('\'', "\\'"));
+
+    if read_to_string(&router_file).ok().as_ref() != Some(&content) {
+        write(&router_file, content).expect("Could not write PHP router script.");
+        debug!("Saved PHP router script at {}", router_file.to_str().unwrap());
+    }
+}
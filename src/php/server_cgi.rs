@@ -1,20 +1,20 @@
+use crate::config::global_config;
+use crate::config::paths;
+use crate::config::project_config;
+use crate::php::ini;
 use crate::php::structs::PhpServerSapi;
-use crate::utils::project_directory::get_rymfony_project_directory;
-use std::fs::create_dir_all;
 use std::fs::File;
 use std::process::Command;
 use std::process::Stdio;
+use std::thread;
+
+const DEFAULT_MIN_WORKERS: usize = 2;
+const DEFAULT_MAX_WORKERS: usize = 8;
 
 pub(crate) fn get_start_command(php_bin: String, port: &u16) -> (PhpServerSapi, Command) {
     let mut command = Command::new(php_bin);
 
-    let log_path = get_rymfony_project_directory().unwrap().join("log");
-
-    if !log_path.is_dir() {
-        create_dir_all(&log_path).expect("Could not create log directory for project");
-    }
-
-    let error_log_file = log_path.join("php-cgi.log");
+    let error_log_file = paths::get_php_cgi_log_file();
 
     if !error_log_file.exists() {
         let file_result = File::create(&error_log_file);
@@ -27,16 +27,33 @@ pub(crate) fn get_start_command(php_bin: String, port: &u16) -> (PhpServerSapi,
         .stdout(Stdio::inherit())
         .stderr(Stdio::inherit())
         .arg("-b") // address:port
-        .arg(format!("127.0.0.1:{}", port.to_string()))
+        .arg(format!("127.0.0.1:{}", port))
         .arg("-d") // INI entries
         .arg(format!("error_log={}", error_log_file.to_str().unwrap()))
         .arg("-e") // extended information for debugger/profiler
     ;
 
+    for setting in ini::get_project_settings() {
+        command.arg("-d").arg(format!("{}={}", setting.name, setting.value));
+    }
+
     // Strangely, php-cgi stops after this amount of requests,
-    // and it has no concurrency, so setting this to a high value
+    // and each worker has no concurrency, so setting this to a high value
     // avoids having to restart php-cgi too much.
     command.env("PHP_FCGI_MAX_REQUESTS", "200000");
 
     (PhpServerSapi::CGI, command)
 }
+
+/// Each PHP-CGI process handles one request at a time, so several of them are started,
+/// one per CPU by default, so that a slow request does not block the whole application.
+pub(crate) fn get_workers_count() -> usize {
+    let configured_workers = project_config::load().cgi.workers.or(global_config::load().cgi.workers);
+
+    match configured_workers {
+        Some(workers) => workers.max(1) as usize,
+        None => thread::available_parallelism()
+            .map_or(DEFAULT_MIN_WORKERS, |cpus| cpus.get())
+            .clamp(DEFAULT_MIN_WORKERS, DEFAULT_MAX_WORKERS),
+    }
+}
@@ -0,0 +1,70 @@
+use crate::php::backend::BackendAddress;
+use crate::php::backend::BackendProtocol;
+use crate::php::backend::PhpBackend;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// A FastCGI server that Rymfony does not manage, like PHP-FPM running in a container.
+/// Its process is neither started, restarted nor stopped by Rymfony.
+pub(crate) struct ExternalBackend {
+    address: BackendAddress,
+    reachable: bool,
+}
+
+impl ExternalBackend {
+    pub(crate) fn new(address: BackendAddress) -> ExternalBackend {
+        ExternalBackend { address, reachable: false }
+    }
+}
+
+impl PhpBackend for ExternalBackend {
+    fn name(&self) -> String {
+        String::from("external PHP")
+    }
+
+    fn protocol(&self) -> BackendProtocol {
+        BackendProtocol::FastCgi
+    }
+
+    fn addresses(&self) -> Vec<BackendAddress> {
+        vec![self.address.clone()]
+    }
+
+    fn log_files(&self) -> Vec<PathBuf> {
+        Vec::new()
+    }
+
+    fn start(&mut self) -> Result<(), Box<dyn Error>> {
+        info!("Using the external PHP server listening to {}", self.address);
+
+        self.reachable = self.address.is_reachable();
+        if !self.reachable {
+            warn!("The external PHP server is not reachable yet at {}.", self.address);
+        }
+
+        Ok(())
+    }
+
+    fn check_health(&mut self) -> Result<(), Box<dyn Error>> {
+        let reachable = self.address.is_reachable();
+
+        if reachable != self.reachable {
+            if reachable {
+                info!("The external PHP server is reachable again at {}.", self.address);
+            } else {
+                warn!("The external PHP server is not reachable at {}.", self.address);
+            }
+            self.reachable = reachable;
+        }
+
+        Ok(())
+    }
+
+    fn reload(&mut self) -> Result<(), Box<dyn Error>> {
+        warn!("The external PHP server is not managed by Rymfony, it must be reloaded on its own.");
+
+        Ok(())
+    }
+
+    fn stop(&mut self) {}
+}
@@ -2,7 +2,11 @@
 use {
     crate::config,
     crate::config::paths,
+    crate::php::fpm_pool,
+    crate::php::ini,
+    crate::php::ini::IniSetting,
     crate::utils::project_directory::get_rymfony_project_directory,
+    crate::utils::prompt,
     regex::Regex,
     regex::RegexBuilder,
     std::error::Error,
@@ -24,9 +28,16 @@ use std::process::Command;
 #[cfg(not(target_family = "windows"))]
 const FPM_DEFAULT_LOG_LEVEL: &str = "notice";
 
+// Increase it when the template changes, so that configs created by older versions can be regenerated.
+#[cfg(not(target_family = "windows"))]
+const FPM_CONFIG_TEMPLATE_VERSION: u32 = 2;
+
 // The placeholders between brackets {{ }} will be replaced with proper values.
 #[cfg(not(target_family = "windows"))]
 const FPM_DEFAULT_CONFIG: &str = "
+; Created by Rymfony from its template version {{ template_version }}.
+; Run \"rymfony serve --reset-config\" to create it again.
+
 [global]
 pid = {{ pid_file }}
 
@@ -47,11 +58,6 @@ listen.allowed_clients = 127.0.0.1
 
 access.log = {{ access_log_file }}
 
-pm = dynamic
-pm.max_children = 5
-pm.start_servers = 2
-pm.min_spare_servers = 1
-pm.max_spare_servers = 3
 pm.status_path = /_fpm-status
 
 ; Output to stderr
@@ -85,36 +91,47 @@ pub(crate) fn get_start_command(php_bin: String, port: &u16) -> (PhpServerSapi,
 
     let config = FPM_DEFAULT_CONFIG
         .replace("{{ port }}", &port.to_string())
+        .replace("{{ template_version }}", &FPM_CONFIG_TEMPLATE_VERSION.to_string())
         .replace("{{ log_level }}", FPM_DEFAULT_LOG_LEVEL)
-        .replace("{{ rymfony_project_dir }}", &rymfony_project_path.to_str().unwrap())
-        .replace("{{ pid_file }}", &config::paths::php_server_pid_file().to_str().unwrap())
+        .replace("{{ rymfony_project_dir }}", rymfony_project_path.to_str().unwrap())
+        .replace("{{ pid_file }}", config::paths::php_server_pid_file().to_str().unwrap())
         .replace("{{ systemd_enable }}", if systemd_support { "" } else { ";" })
-        .replace("{{ access_log_file }}", &config::paths::get_php_server_log_file().to_str().unwrap())
-        .replace("{{ error_log_file }}", &config::paths::get_php_server_error_file().to_str().unwrap());
+        .replace("{{ access_log_file }}", config::paths::get_php_server_log_file().to_str().unwrap())
+        .replace("{{ error_log_file }}", config::paths::get_php_server_error_file().to_str().unwrap());
 
     let fpm_config_file_path = config::paths::php_fpm_conf_ini_file();
 
-    if !fpm_config_file_path.exists() {
+    let existing_content = if fpm_config_file_path.exists() {
+        Some(read_to_string(&fpm_config_file_path).unwrap())
+    } else {
+        None
+    };
+
+    let content = match &existing_content {
+        None => config,
+        Some(content) => {
+            // Read the file and search the port
+            let port_used = read_port(content).unwrap_or(*port);
+
+            if &port_used != port {
+                // If the port is different in the config file than in the current execution,
+                // we rewrite the whole config, but only changing the port.
+                change_port(content, port)
+            } else {
+                content.clone()
+            }
+        },
+    };
+
+    let content = fpm_pool::apply_pool_settings(&content, &fpm_pool::get_pool_settings());
+    let content = apply_php_ini_settings(&content, &ini::get_project_settings());
+
+    if existing_content.as_ref() != Some(&content) {
         let mut fpm_config_file = File::create(&fpm_config_file_path).unwrap();
-        fpm_config_file.write_all(config.as_bytes()).expect("Could not write to php-fpm config file.");
+        fpm_config_file.write_all(content.as_bytes()).unwrap_or_else(|_| {
+            panic!("Could not write to php-fpm config file {}.", &fpm_config_file_path.to_str().unwrap())
+        });
         debug!("Saved FPM config file at {}", fpm_config_file_path.to_str().unwrap());
-    } else {
-        // Read the file and search the port
-        let content = read_to_string(&fpm_config_file_path).unwrap();
-
-        let port_used = read_port(&content).unwrap_or(port.clone());
-
-        if &port_used != port {
-            // If the port is different in the config file than in the current execution,
-            // we rewrite the whole config, but only changing the port.
-            let content = change_port(&content, &port);
-            remove_file(&fpm_config_file_path).expect("Could not remove php-fpm config file");
-            let mut fpm_config_file = File::create(&fpm_config_file_path).unwrap();
-            fpm_config_file.write_all(content.as_bytes()).expect(
-                format!("Could not write to php-fpm config file {}.", &fpm_config_file_path.to_str().unwrap()).as_str(),
-            );
-            debug!("Rewrote FPM config file at {}", fpm_config_file_path.to_str().unwrap());
-        }
     }
 
     let mut file_options = OpenOptions::new();
@@ -140,6 +157,134 @@ pub(crate) fn get_start_command(php_bin: String, port: &u16) -> (PhpServerSapi,
     (PhpServerSapi::FPM, command)
 }
 
+/// Offers to regenerate the FPM config when it was created from an older template,
+/// since it lacks the settings Rymfony relies on.
+#[cfg(not(target_family = "windows"))]
+pub(crate) fn check_config_template() {
+    let fpm_config_file_path = config::paths::php_fpm_conf_ini_file();
+
+    let content = match read_to_string(&fpm_config_file_path) {
+        Ok(content) => content,
+        Err(_) => return,
+    };
+
+    if read_template_version(&content).unwrap_or(0) >= FPM_CONFIG_TEMPLATE_VERSION {
+        return;
+    }
+
+    warn!(
+        "The PHP-FPM config \"{}\" was created by an older version of Rymfony.",
+        fpm_config_file_path.to_str().unwrap()
+    );
+
+    if prompt::confirm("Do you want to regenerate it? Your changes to this file will be lost.", false) {
+        remove_file(&fpm_config_file_path).expect("Could not remove PHP-FPM config file.");
+        info!("The PHP-FPM config will be created again.");
+    } else {
+        info!("Keeping it, you can run \"rymfony serve --reset-config\" to regenerate it later.");
+    }
+}
+
+#[cfg(not(target_family = "windows"))]
+fn read_template_version(content: &str) -> Option<u32> {
+    let re =
+        RegexBuilder::new(r"^; Created by Rymfony from its template version (\d+)\.").multi_line(true).build().unwrap();
+
+    re.captures(content).and_then(|captures| captures[1].parse().ok())
+}
+
+#[cfg(not(target_family = "windows"))]
+#[derive(Debug)]
+pub(crate) struct FpmConfigError(String);
+
+#[cfg(not(target_family = "windows"))]
+impl fmt::Display for FpmConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "An error occured: {}", self.0)
+    }
+}
+#[cfg(not(target_family = "windows"))]
+impl Error for FpmConfigError {}
+
+/// Runs the config test of PHP-FPM, so that a broken config is reported with its faulty line,
+/// instead of making the server exit right after starting.
+#[cfg(not(target_family = "windows"))]
+pub(crate) fn test_config(php_bin: &str) -> Result<(), FpmConfigError> {
+    let fpm_config_file_path = config::paths::php_fpm_conf_ini_file();
+
+    let mut command = Command::new(php_bin);
+    command.arg("-t").arg("--fpm-config").arg(&fpm_config_file_path);
+    if get_current_uid() == 0 {
+        command.arg("--allow-to-run-as-root");
+    }
+
+    let output = match command.output() {
+        Ok(output) => output,
+        Err(e) => {
+            warn!("Could not test the PHP-FPM config: {}", e);
+            return Ok(());
+        },
+    };
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let test_output =
+        format!("{}\n{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    let content = read_to_string(&fpm_config_file_path).unwrap_or_default();
+
+    Err(FpmConfigError(format!(
+        "The PHP-FPM config \"{}\" is invalid: {}",
+        fpm_config_file_path.to_str().unwrap(),
+        describe_config_test_error(&test_output, &content)
+    )))
+}
+
+/// Makes the PHP-FPM master process reload its config and php.ini, and replace its workers gracefully,
+/// letting them finish the requests they are handling.
+#[cfg(not(target_family = "windows"))]
+pub(crate) fn reload(pid: u32) -> std::io::Result<()> {
+    let status = Command::new("kill")
+        .stderr(Stdio::null())
+        .stdout(Stdio::null())
+        .arg("-USR2")
+        .arg("--")
+        .arg(pid.to_string())
+        .status()?;
+
+    if !status.success() {
+        return Err(std::io::Error::other(format!("Could not signal PHP-FPM with PID {}", pid)));
+    }
+
+    Ok(())
+}
+
+/// Extracts the first error of "php-fpm -t", with the faulty line of the config when it is known.
+#[cfg(not(target_family = "windows"))]
+fn describe_config_test_error(test_output: &str, content: &str) -> String {
+    let located_error = Regex::new(r"ERROR: \[[^\]]+:(\d+)\] (.+)").unwrap();
+    let syntax_error = Regex::new(r"(?:PHP:\s+)?(.+) in \S+ on line (\d+)").unwrap();
+    let any_error = Regex::new(r"ERROR: (.+)").unwrap();
+
+    let (line_number, message) = if let Some(captures) = located_error.captures(test_output) {
+        (captures[1].parse::<usize>().ok(), captures[2].trim().to_string())
+    } else if let Some(captures) = syntax_error.captures(test_output) {
+        (captures[2].parse::<usize>().ok(), captures[1].trim().to_string())
+    } else if let Some(captures) = any_error.captures(test_output) {
+        (None, captures[1].trim().to_string())
+    } else {
+        (None, test_output.trim().to_string())
+    };
+
+    let line = line_number.and_then(|number| content.lines().nth(number.wrapping_sub(1)).map(|line| (number, line)));
+
+    match line {
+        Some((number, line)) => format!("{} (line {})\n    {} | {}", message, number, number, line.trim_end()),
+        None => message,
+    }
+}
+
 #[cfg(not(target_family = "windows"))]
 #[derive(Debug)]
 struct ReadPortError(String);
@@ -162,10 +307,9 @@ fn read_port(content: &str) -> std::result::Result<u16, ReadPortError> {
     let mut read_port = "".to_string();
     for caps in re.captures_iter(content) {
         let captures = regex_port.captures(&caps[1]);
-        if !captures.is_none() && !found {
+        if let (Some(capss), false) = (captures, found) {
             found = true;
-            let capss = captures.unwrap();
-            read_port = format!("{}", &capss[1]);
+            read_port = capss[1].to_string();
         }
     }
     if !found {
@@ -188,14 +332,44 @@ fn change_port(original_content: &str, new_port: &u16) -> String {
         if captures.is_none() || found {
             content = content.replace(&caps[0], format!(";{}", &caps[0]).as_str());
         }
-        if !captures.is_none() && !found {
+        if let (Some(capss), false) = (captures, found) {
             found = true;
-            let capss = captures.unwrap();
-            content = content.replace(&caps[0], format!("{}{}{}", &caps[1], &capss[1], new_port.to_string()).as_str());
+            content = content.replace(&caps[0], format!("{}{}{}", &caps[1], &capss[1], new_port).as_str());
         }
     }
     if !found {
-        content = format!("{}\nlisten = 127.0.0.1:{}", content, new_port.to_string());
+        content = format!("{}\nlisten = 127.0.0.1:{}", content, new_port);
+    }
+
+    content
+}
+
+#[cfg(not(target_family = "windows"))]
+const PHP_INI_SETTINGS_START: &str = "; Project PHP settings, managed by Rymfony: changes in this block will be lost.";
+#[cfg(not(target_family = "windows"))]
+const PHP_INI_SETTINGS_END: &str = "; End of project PHP settings";
+
+/// Replaces the block of project INI settings at the end of the FPM config, so they apply to the "www" pool.
+#[cfg(not(target_family = "windows"))]
+fn apply_php_ini_settings(original_content: &str, settings: &[IniSetting]) -> String {
+    let mut content = original_content.to_string();
+    fpm_pool::remove_managed_block(&mut content, PHP_INI_SETTINGS_START, PHP_INI_SETTINGS_END);
+
+    let mut content = format!("{}\n", content.trim_end());
+
+    if !settings.is_empty() {
+        content.push('\n');
+        content.push_str(PHP_INI_SETTINGS_START);
+        content.push('\n');
+        for setting in settings {
+            content.push_str(&format!(
+                "php_admin_value[{}] = \"{}\"\n",
+                setting.name,
+                setting.value.replace('"', "\\\"")
+            ));
+        }
+        content.push_str(PHP_INI_SETTINGS_END);
+        content.push('\n');
     }
 
     content
@@ -206,11 +380,79 @@ fn change_port(original_content: &str, new_port: &u16) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn php_ini_settings_are_replaced() {
+        let settings = vec![
+            IniSetting { name: String::from("memory_limit"), value: String::from("512M"), source: "php.ini" },
+            IniSetting { name: String::from("date.timezone"), value: String::from("UTC"), source: "php.ini" },
+        ];
+
+        let content = apply_php_ini_settings("[www]\nlisten = 127.0.0.1:60000\n", &settings);
+        assert_eq!(
+            content,
+            format!(
+                "[www]\nlisten = 127.0.0.1:60000\n\n{}\nphp_admin_value[memory_limit] = \"512M\"\nphp_admin_value[date.timezone] = \"UTC\"\n{}\n",
+                PHP_INI_SETTINGS_START, PHP_INI_SETTINGS_END
+            )
+        );
+
+        let content = apply_php_ini_settings(&content, &settings[..1]);
+        assert_eq!(content.matches("php_admin_value").count(), 1);
+
+        let content = apply_php_ini_settings(&content, &[]);
+        assert_eq!(content, "[www]\nlisten = 127.0.0.1:60000\n");
+    }
+
+    #[test]
+    fn managed_blocks_are_stable() {
+        let pool_settings = fpm_pool::PoolSettings {
+            pm: "dynamic",
+            max_children: 5,
+            start_servers: 2,
+            min_spare_servers: 1,
+            max_spare_servers: 3,
+            max_requests: None,
+            request_terminate_timeout: None,
+            request_slowlog_timeout: None,
+            slowlog: None,
+        };
+        let ini_settings =
+            vec![IniSetting { name: String::from("memory_limit"), value: String::from("512M"), source: "php.ini" }];
+        let apply = |content: &str| {
+            apply_php_ini_settings(&fpm_pool::apply_pool_settings(content, &pool_settings), &ini_settings)
+        };
+
+        let content = apply("[www]\nlisten = 127.0.0.1:60000\n");
+
+        assert_eq!(apply(&content), content);
+        assert_eq!(apply(&apply(&content)), content);
+    }
+
+    #[test]
+    fn config_test_errors_point_to_the_faulty_line() {
+        let content = "[global]\ndaemonize = no\n\n[www]\npm.max_children = many\n";
+
+        let output = "[19-Oct-2026 10:00:00] ERROR: [/home/me/.rymfony/abc/fpm-conf.ini:5] unable to parse value for entry 'pm.max_children'\n[19-Oct-2026 10:00:00] ERROR: failed to load configuration file '/home/me/.rymfony/abc/fpm-conf.ini'\n";
+        assert_eq!(
+            describe_config_test_error(output, content),
+            "unable to parse value for entry 'pm.max_children' (line 5)\n    5 | pm.max_children = many"
+        );
+
+        let output = "PHP:  syntax error, unexpected '=' in /home/me/.rymfony/abc/fpm-conf.ini on line 2\n[19-Oct-2026 10:00:00] ERROR: failed to load configuration file\n";
+        assert_eq!(
+            describe_config_test_error(output, content),
+            "syntax error, unexpected '=' (line 2)\n    2 | daemonize = no"
+        );
+
+        let output = "[19-Oct-2026 10:00:00] ERROR: [pool www] pm.max_children must be a positive value\n";
+        assert_eq!(describe_config_test_error(output, content), "[pool www] pm.max_children must be a positive value");
+    }
+
     #[test]
     fn change_port_one_line() {
         let str = "listen=1245";
         let port = 2316;
-        let result = change_port(&str, &port);
+        let result = change_port(str, &port);
         assert_eq!(result.as_str(), "listen=2316");
     }
     #[test]
@@ -219,7 +461,7 @@ mod tests {
         listen = 127.0.0.1:1245
         ";
         let port = 2316;
-        let result = change_port(&str, &port);
+        let result = change_port(str, &port);
         assert_eq!(
             result.as_str(),
             "
@@ -235,7 +477,7 @@ listen = 127.0.0.1:158
 listen =localhost:18
         ";
         let port = 2316;
-        let result = change_port(&str, &port);
+        let result = change_port(str, &port);
         assert_eq!(
             result.as_str(),
             "
@@ -251,7 +493,7 @@ listen = 127.0.0.1:2316
 listen = /path/to/socket
         ";
         let port = 2316;
-        let result = change_port(&str, &port);
+        let result = change_port(str, &port);
         assert_eq!(
             result.as_str(),
             "
@@ -0,0 +1,141 @@
+use crate::config::paths;
+use colored::*;
+use regex::Regex;
+use regex::RegexBuilder;
+use std::fs::read_to_string;
+use std::path::Path;
+
+/// A request reported by the slow log of PHP-FPM, with the PHP backtrace at the time it exceeded the timeout.
+#[derive(Debug, Default, PartialEq)]
+pub(crate) struct SlowLogEntry {
+    pub(crate) date: String,
+    pub(crate) pid: String,
+    pub(crate) script: String,
+    pub(crate) backtrace: Vec<String>,
+}
+
+/// Groups the lines of the slow log into entries, as they are read.
+pub(crate) struct SlowLogParser {
+    header_regex: Regex,
+    frame_regex: Regex,
+    entry: Option<SlowLogEntry>,
+}
+
+impl SlowLogParser {
+    pub(crate) fn new() -> SlowLogParser {
+        SlowLogParser {
+            header_regex: Regex::new(r"^\[([^\]]+)\]\s+\[pool [^\]]+\] pid (\d+)$").unwrap(),
+            frame_regex: Regex::new(r"^\[0x[0-9a-fA-F]+\] (.+)$").unwrap(),
+            entry: None,
+        }
+    }
+
+    /// Adds a line of the slow log. Returns the previous entry when this line starts a new one.
+    pub(crate) fn push(&mut self, line: &str) -> Option<SlowLogEntry> {
+        let line = line.trim_end();
+
+        if let Some(captures) = self.header_regex.captures(line) {
+            let entry =
+                SlowLogEntry { date: captures[1].to_string(), pid: captures[2].to_string(), ..Default::default() };
+
+            return self.entry.replace(entry);
+        }
+
+        if let Some(entry) = self.entry.as_mut() {
+            if let Some(script) = line.strip_prefix("script_filename = ") {
+                entry.script = script.to_string();
+            } else if let Some(captures) = self.frame_regex.captures(line) {
+                entry.backtrace.push(captures[1].to_string());
+            }
+        }
+
+        None
+    }
+
+    /// Returns the entry being read, once no more lines are expected for it.
+    pub(crate) fn flush(&mut self) -> Option<SlowLogEntry> {
+        self.entry.take()
+    }
+}
+
+pub(crate) fn parse(content: &str) -> Vec<SlowLogEntry> {
+    let mut parser = SlowLogParser::new();
+
+    let mut entries: Vec<SlowLogEntry> = content.lines().filter_map(|line| parser.push(line)).collect();
+    entries.extend(parser.flush());
+
+    entries
+}
+
+/// Returns the timeout of the slow log, as written in the PHP-FPM config of the project.
+pub(crate) fn get_timeout() -> Option<String> {
+    let content = read_to_string(paths::php_fpm_conf_ini_file()).ok()?;
+    let re = RegexBuilder::new(r"^[ \t]*request_slowlog_timeout[ \t]*=[ \t]*(\S+)").multi_line(true).build().unwrap();
+
+    re.captures(&content).map(|captures| captures[1].to_string())
+}
+
+/// Renders an entry as a single block, with paths relative to the project directory.
+pub(crate) fn render(entry: &SlowLogEntry, timeout: Option<&str>, project_dir: &Path) -> String {
+    let relative = |path: &str| -> String {
+        Path::new(path).strip_prefix(project_dir).map_or(path.to_string(), |path| path.display().to_string())
+    };
+
+    let duration = match timeout {
+        Some(timeout) if timeout.chars().all(|c| c.is_ascii_digit()) => format!(" (over {}s)", timeout),
+        Some(timeout) => format!(" (over {})", timeout),
+        None => String::new(),
+    };
+
+    let mut block = format!(
+        "{} {}{}, pid {}\n  {}\n",
+        "Slow request".yellow().bold(),
+        entry.date,
+        duration,
+        entry.pid,
+        relative(&entry.script).bold()
+    );
+
+    for (index, frame) in entry.backtrace.iter().enumerate() {
+        let frame = match frame.rsplit_once(' ') {
+            Some((function, location)) => format!("{} {}", function, relative(location).dimmed()),
+            None => frame.to_string(),
+        };
+        block.push_str(&format!("    #{} {}\n", index, frame));
+    }
+
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_grouped_with_their_backtrace() {
+        let content = "
+[19-Oct-2026 10:00:00]  [pool www] pid 1234
+script_filename = /app/public/index.php
+[0x00007f1e2e214e20] sleep() /app/src/Controller/HomeController.php:12
+[0x00007f1e2e214d00] index() /app/vendor/symfony/http-kernel/HttpKernel.php:163
+
+[19-Oct-2026 10:00:07]  [pool www] pid 1235
+script_filename = /app/public/index.php
+[0x00007f1e2e214e20] curl_exec() /app/src/Api/Client.php:40
+";
+
+        let entries = parse(content);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].pid, "1234");
+        assert_eq!(entries[0].backtrace.len(), 2);
+        assert_eq!(entries[1].date, "19-Oct-2026 10:00:07");
+        assert_eq!(entries[1].backtrace, vec!["curl_exec() /app/src/Api/Client.php:40"]);
+
+        colored::control::set_override(false);
+        assert_eq!(
+            render(&entries[1], Some("5"), Path::new("/app")),
+            "Slow request 19-Oct-2026 10:00:07 (over 5s), pid 1235\n  public/index.php\n    #0 curl_exec() src/Api/Client.php:40\n"
+        );
+    }
+}
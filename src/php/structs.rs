@@ -1,8 +1,15 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::Display;
 use std::fmt::Formatter;
 use std::fmt::Result as FmtResult;
+use std::fs::metadata;
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
 
+use crate::php::build_info;
+use crate::php::build_info::PhpBuildInfo;
 use regex::Regex;
 use serde::de::Deserialize;
 use serde::de::Deserializer;
@@ -12,7 +19,8 @@ use serde::Deserialize as SerdeDeserialize;
 use serde::Serialize;
 use serde::Serializer;
 
-#[derive(Clone, Copy, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub(crate) enum PhpServerSapi {
     FPM,
     CGI,
@@ -58,36 +66,117 @@ impl Display for PhpServerSapi {
 //
 //
 
-#[derive(Hash, Eq, PartialEq, Debug)]
+#[derive(Debug)]
+pub(crate) struct PhpVersionError(String);
+
+impl Display for PhpVersionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "An error occured: {}", self.0)
+    }
+}
+
+impl std::error::Error for PhpVersionError {}
+
+/// Pre-release stages, in the order they are published.
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub(crate) enum PreReleaseStage {
+    Dev,
+    Alpha,
+    Beta,
+    RC,
+}
+
+/// A PHP version like "8.2.10", "8.4.0RC1", "8.3.0-dev" or "8.1.2-1ubuntu2.14".
+///
+/// The original string is kept, so that versions are displayed and stored as PHP reports them.
+#[derive(Clone, Hash, Eq, PartialEq, Debug)]
 pub(crate) struct PhpVersion {
+    major: u64,
+    minor: u64,
+    patch: u64,
+    pre_release: Option<(PreReleaseStage, u64)>,
+    /// Anything after the version and pre-release, like a distribution package revision.
+    extra: String,
     _version: String,
 }
 
 impl PhpVersion {
-    pub(crate) fn clone(&self) -> PhpVersion {
-        PhpVersion { _version: self._version.clone() }
+    pub(crate) fn version(&self) -> &str {
+        self._version.as_str()
     }
 
-    pub(crate) fn from_string(version: String) -> PhpVersion {
-        let version_regex = Regex::new("^[578]\\.\\d{1,2}\\.\\d+$").unwrap();
+    pub(crate) fn major(&self) -> u64 {
+        self.major
+    }
 
-        if !version_regex.is_match(&version) {
-            panic!("Version \"{}\" is not a valid PHP version.", &version);
-        }
+    pub(crate) fn minor(&self) -> u64 {
+        self.minor
+    }
 
-        PhpVersion { _version: version.clone() }
+    pub(crate) fn is_pre_release(&self) -> bool {
+        self.pre_release.is_some()
     }
+}
 
-    pub(crate) fn new() -> PhpVersion {
-        PhpVersion { _version: "".to_string() }
+impl FromStr for PhpVersion {
+    type Err = PhpVersionError;
+
+    fn from_str(version: &str) -> Result<PhpVersion, PhpVersionError> {
+        let version_regex = Regex::new(r"^(\d+)\.(\d+)\.(\d+)(?:[-.]?(?i:(dev|alpha|beta|RC))(\d*))?(.*)$").unwrap();
+
+        let captures = version_regex
+            .captures(version)
+            .ok_or_else(|| PhpVersionError(format!("Version \"{}\" is not a valid PHP version.", version)))?;
+
+        let number = |index: usize| {
+            captures[index]
+                .parse::<u64>()
+                .map_err(|_| PhpVersionError(format!("Version \"{}\" is not a valid PHP version.", version)))
+        };
+
+        let pre_release = match captures.get(4) {
+            Some(stage) => {
+                let stage = match stage.as_str().to_lowercase().as_str() {
+                    "dev" => PreReleaseStage::Dev,
+                    "alpha" => PreReleaseStage::Alpha,
+                    "beta" => PreReleaseStage::Beta,
+                    _ => PreReleaseStage::RC,
+                };
+                Some((stage, captures[5].parse::<u64>().unwrap_or(0)))
+            },
+            None => None,
+        };
+
+        Ok(PhpVersion {
+            major: number(1)?,
+            minor: number(2)?,
+            patch: number(3)?,
+            pre_release,
+            extra: captures[6].to_string(),
+            _version: version.to_string(),
+        })
     }
+}
 
-    pub(crate) fn from_str(version: &str) -> PhpVersion {
-        PhpVersion::from_string(version.to_string())
+impl Ord for PhpVersion {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.major, self.minor, self.patch)
+            .cmp(&(other.major, other.minor, other.patch))
+            // A pre-release comes before the release itself.
+            .then_with(|| match (&self.pre_release, &other.pre_release) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(pre_release), Some(other_pre_release)) => pre_release.cmp(other_pre_release),
+            })
+            .then_with(|| self.extra.cmp(&other.extra))
+            .then_with(|| self._version.cmp(&other._version))
     }
+}
 
-    pub(crate) fn version(&self) -> &str {
-        self._version.as_str()
+impl PartialOrd for PhpVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
     }
 }
 
@@ -96,7 +185,7 @@ impl Serialize for PhpVersion {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self._version.as_str())
+        serializer.serialize_str(self._version.as_str())
     }
 }
 
@@ -122,7 +211,7 @@ impl<'de> Visitor<'de> for PhpVersionVisitor {
     where
         E: Error,
     {
-        Ok(PhpVersion::from_str(value))
+        value.parse().map_err(E::custom)
     }
 }
 
@@ -135,33 +224,43 @@ impl<'de> Visitor<'de> for PhpVersionVisitor {
 //
 //
 
-#[derive(Hash, Eq, PartialEq, Debug, Serialize, SerdeDeserialize)]
+/// A PHP installation, identified by its version, its directory and its build flavor,
+/// so that several builds of the same version can be installed side by side.
+#[derive(Clone, Hash, Eq, PartialEq, Debug, Serialize, SerdeDeserialize)]
 pub(crate) struct PhpBinary {
+    version: PhpVersion,
+    directory: String,
+    flavor: String,
     cli: String,
     fpm: String,
     cgi: String,
     system: bool,
-    #[serde(skip_serializing)]
-    #[serde(default = "PhpVersion::new")]
-    _version: PhpVersion,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    fingerprints: BTreeMap<String, FileFingerprint>,
+    #[serde(default)]
+    build: PhpBuildInfo,
 }
 
 impl PhpBinary {
-    pub(crate) fn from_version(version: PhpVersion) -> PhpBinary {
+    pub(crate) fn new(version: PhpVersion, directory: &str, flavor: &str) -> PhpBinary {
         PhpBinary {
-            _version: version,
+            version,
+            directory: directory.to_string(),
+            flavor: flavor.to_string(),
             cli: String::from(""),
             fpm: String::from(""),
             cgi: String::from(""),
             system: false,
+            source: String::from(""),
+            fingerprints: BTreeMap::new(),
+            build: PhpBuildInfo::default(),
         }
     }
 
+    /// Adds the binaries of another installation that this one does not have.
     pub(crate) fn merge_with(&mut self, from: PhpBinary) {
-        if self._version != from._version {
-            return;
-        }
-
         for sapi in PhpServerSapi::all() {
             if from.has_sapi(&sapi) && !self.has_sapi(&sapi) {
                 self.add_sapi(&sapi, &from.sapi_path(&sapi));
@@ -169,6 +268,28 @@ impl PhpBinary {
         }
     }
 
+    pub(crate) fn is_same_installation(&self, other: &PhpBinary) -> bool {
+        self.version == other.version && self.directory == other.directory && self.flavor == other.flavor
+    }
+
+    pub(crate) fn version(&self) -> &PhpVersion {
+        &self.version
+    }
+
+    /// Installation prefix of the binaries, like "/usr" for "/usr/bin/php" and "/usr/sbin/php-fpm".
+    pub(crate) fn directory(&self) -> &String {
+        &self.directory
+    }
+
+    /// Thread safety and debug mode of the build, like "NTS" or "ZTS debug".
+    pub(crate) fn flavor(&self) -> &String {
+        &self.flavor
+    }
+
+    pub(crate) fn is_debug(&self) -> bool {
+        self.flavor.ends_with("debug")
+    }
+
     pub(crate) fn cli(&self) -> &String {
         &self.cli
     }
@@ -181,33 +302,62 @@ impl PhpBinary {
 
     pub(crate) fn has_sapi(&self, sapi: &PhpServerSapi) -> bool {
         match sapi {
-            PhpServerSapi::FPM => self.fpm != "",
-            PhpServerSapi::CLI => self.cli != "",
-            PhpServerSapi::CGI => self.cgi != "",
+            PhpServerSapi::FPM => !self.fpm.is_empty(),
+            PhpServerSapi::CLI => !self.cli.is_empty(),
+            PhpServerSapi::CGI => !self.cgi.is_empty(),
             PhpServerSapi::Unknown => false,
         }
     }
 
+    /// Name of the discovery source that found this binary, like "PATH" or "Homebrew".
+    pub(crate) fn source(&self) -> &String {
+        &self.source
+    }
+
+    pub(crate) fn set_source(&mut self, source: &str) {
+        self.source = source.to_string();
+    }
+
+    pub(crate) fn build(&self) -> &PhpBuildInfo {
+        &self.build
+    }
+
+    /// Collects build details, from the CLI binary when available since it is the one used by "php -m" in a terminal.
+    pub(crate) fn probe_build(&mut self) {
+        let binary = if !self.cli.is_empty() { Some(self.cli.clone()) } else { self.preferred_sapi() };
+
+        if let Some(build) = binary.and_then(|binary| build_info::probe(&binary)) {
+            self.build = build;
+        }
+    }
+
     pub(crate) fn system(&self) -> bool {
-        self.system.clone()
+        self.system
     }
 
     pub(crate) fn set_system(&mut self, is_system: bool) {
         self.system = is_system;
     }
 
-    pub(crate) fn preferred_sapi(&self) -> String {
-        if self.fpm != "" {
-            return self.fpm.clone();
-        } else if self.cgi != "" {
-            return self.cgi.clone();
-        } else if self.cli != "" {
-            return self.cli.clone();
+    /// Returns the SAPI that can be used to run a PHP server, if any.
+    /// FPM is not supported on Windows, and the CLI runs PHP's built-in web server.
+    pub(crate) fn server_sapi(&self) -> Option<PhpServerSapi> {
+        if !self.fpm.is_empty() && cfg!(not(target_family = "windows")) {
+            Some(PhpServerSapi::FPM)
+        } else if !self.cgi.is_empty() {
+            Some(PhpServerSapi::CGI)
+        } else if !self.cli.is_empty() {
+            Some(PhpServerSapi::CLI)
         } else {
-            panic!("Cannot detect preferred SAPI for PHP \"{}\"", self._version.version());
+            None
         }
     }
 
+    /// Returns the path of the best binary of the installation, if it has any.
+    pub(crate) fn preferred_sapi(&self) -> Option<String> {
+        [&self.fpm, &self.cgi, &self.cli].iter().find(|path| !path.is_empty()).map(|path| path.to_string())
+    }
+
     pub(crate) fn add_sapi(&mut self, sapi: &PhpServerSapi, path: &String) {
         match sapi {
             PhpServerSapi::FPM => {
@@ -220,9 +370,34 @@ impl PhpBinary {
                 self.cgi = path.clone();
             },
             PhpServerSapi::Unknown => {
-                panic!("Unknown SAPI \"{}\" at path \"{}\"", &sapi, &path);
+                warn!("Ignoring PHP binary \"{}\": its SAPI is not supported.", &path);
+                return;
             },
         }
+
+        if let Some(fingerprint) = FileFingerprint::from_path(path) {
+            self.fingerprints.insert(path.clone(), fingerprint);
+        }
+    }
+
+    pub(crate) fn remove_sapi(&mut self, sapi: &PhpServerSapi) {
+        let path = self.sapi_path(sapi);
+        self.fingerprints.remove(&path);
+
+        match sapi {
+            PhpServerSapi::FPM => self.fpm.clear(),
+            PhpServerSapi::CLI => self.cli.clear(),
+            PhpServerSapi::CGI => self.cgi.clear(),
+            PhpServerSapi::Unknown => {},
+        }
+    }
+
+    /// Whether the file at this path is the same as when it was added to this binary.
+    pub(crate) fn is_unchanged(&self, path: &str) -> bool {
+        match (self.fingerprints.get(path), FileFingerprint::from_path(path)) {
+            (Some(known), Some(current)) => known == &current,
+            _ => false,
+        }
     }
 
     pub(crate) fn sapi_path(&self, sapi: &PhpServerSapi) -> String {
@@ -234,3 +409,79 @@ impl PhpBinary {
         }
     }
 }
+
+impl Display for PhpBinary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{} ({}, {})", self.version.version(), self.flavor, self.directory)
+    }
+}
+
+/// Modification time and size of a binary, to detect when it is replaced, like during a package upgrade.
+#[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, SerdeDeserialize)]
+pub(crate) struct FileFingerprint {
+    mtime: u64,
+    size: u64,
+}
+
+impl FileFingerprint {
+    pub(crate) fn from_path(path: &str) -> Option<FileFingerprint> {
+        let metadata = metadata(path).ok()?;
+        let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_millis() as u64;
+
+        Some(FileFingerprint { mtime, size: metadata.len() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(version: &str) -> PhpVersion {
+        version.parse().unwrap()
+    }
+
+    #[test]
+    fn versions_with_suffixes_are_parsed() {
+        let distribution = version("8.1.2-1ubuntu2.14");
+        assert_eq!((distribution.major(), distribution.minor()), (8, 1));
+        assert!(!distribution.is_pre_release());
+        assert_eq!(distribution.version(), "8.1.2-1ubuntu2.14");
+
+        assert!(version("8.4.0RC1").is_pre_release());
+        assert!(version("8.3.0-dev").is_pre_release());
+        assert_eq!(version("10.0.0").major(), 10);
+
+        assert!("8.2".parse::<PhpVersion>().is_err());
+        assert!("latest".parse::<PhpVersion>().is_err());
+    }
+
+    #[test]
+    fn versions_are_ordered_semantically() {
+        let mut versions = [
+            version("8.10.0"),
+            version("8.4.0"),
+            version("8.4.0RC2"),
+            version("8.4.0RC1"),
+            version("8.4.0beta3"),
+            version("8.4.0-dev"),
+            version("8.2.10"),
+            version("9.0.0"),
+        ];
+        versions.sort();
+
+        let versions: Vec<&str> = versions.iter().map(|version| version.version()).collect();
+        assert_eq!(
+            versions,
+            vec![
+                "8.2.10",
+                "8.4.0-dev",
+                "8.4.0beta3",
+                "8.4.0RC1",
+                "8.4.0RC2",
+                "8.4.0",
+                "8.10.0",
+                "9.0.0"
+            ]
+        );
+    }
+}
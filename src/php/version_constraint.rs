@@ -0,0 +1,341 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
+
+/// Major, minor and patch numbers of a version.
+pub(crate) type VersionNumbers = (u64, u64, u64);
+
+#[derive(Debug)]
+pub(crate) struct VersionConstraintError(String);
+
+impl fmt::Display for VersionConstraintError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "An error occured: {}", self.0)
+    }
+}
+
+impl Error for VersionConstraintError {}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Comparator {
+    operator: Operator,
+    version: VersionNumbers,
+}
+
+impl Comparator {
+    fn new(operator: Operator, version: VersionNumbers) -> Comparator {
+        Comparator { operator, version }
+    }
+
+    fn matches(&self, version: &VersionNumbers) -> bool {
+        let ordering = version.cmp(&self.version);
+
+        match self.operator {
+            Operator::Equal => ordering == Ordering::Equal,
+            Operator::NotEqual => ordering != Ordering::Equal,
+            Operator::GreaterThan => ordering == Ordering::Greater,
+            Operator::GreaterThanOrEqual => ordering != Ordering::Less,
+            Operator::LessThan => ordering == Ordering::Less,
+            Operator::LessThanOrEqual => ordering != Ordering::Greater,
+        }
+    }
+}
+
+/// A PHP version constraint, using the same syntax as Composer.
+///
+/// Supported syntaxes are:
+///  * Partial versions and wildcards: `8.2`, `8.*`, `8.2.*` (any 8.2 version)
+///  * Exact versions: `8.2.10`
+///  * Comparisons: `>=8.1`, `<8.3`, `!=8.1.2`, separated by spaces or commas for "and": `>=8.1 <8.3`
+///  * Caret and tilde ranges: `^8.1`, `~8.1.2`
+///  * Hyphen ranges, including the whole upper partial version: `8.1 - 8.3`
+///  * Alternatives with `||`: `^7.4 || ^8.0`
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct VersionConstraint {
+    alternatives: Vec<Vec<Comparator>>,
+}
+
+impl VersionConstraint {
+    pub(crate) fn parse(constraint: &str) -> Result<VersionConstraint, VersionConstraintError> {
+        let mut alternatives = Vec::new();
+
+        for alternative in constraint.split("||").flat_map(|part| part.split('|')) {
+            let mut comparators = Vec::new();
+            let mut terms = alternative.split(|c: char| c == ',' || c.is_whitespace()).filter(|term| !term.is_empty());
+
+            while let Some(term) = terms.next() {
+                let mut term = term.to_string();
+
+                // An operator may be separated from its version, like in ">= 8.1".
+                if OPERATORS.contains(&term.as_str()) {
+                    term.push_str(terms.next().unwrap_or(""));
+                }
+
+                if terms.clone().next() == Some("-") {
+                    terms.next();
+                    let upper_term = terms.next().unwrap_or("");
+                    comparators.extend(parse_hyphen_range(&term, upper_term)?);
+                } else {
+                    comparators.extend(parse_term(&term)?);
+                }
+            }
+
+            if comparators.is_empty() {
+                return Err(VersionConstraintError(format!("Empty version constraint in \"{}\"", constraint)));
+            }
+
+            alternatives.push(comparators);
+        }
+
+        Ok(VersionConstraint { alternatives })
+    }
+
+    /// Creates a constraint matching all versions of the same minor version (like "8.1.*").
+    pub(crate) fn same_minor_as(version: &str) -> Result<VersionConstraint, VersionConstraintError> {
+        let (major, minor, _) = parse_version_numbers(version)
+            .ok_or_else(|| VersionConstraintError(format!("Invalid version \"{}\"", version)))?;
+
+        VersionConstraint::parse(&format!("{}.{}.*", major, minor))
+    }
+
+    pub(crate) fn matches(&self, version: &str) -> bool {
+        let version = match parse_version_numbers(version) {
+            Some(version) => version,
+            None => return false,
+        };
+
+        self.alternatives.iter().any(|comparators| comparators.iter().all(|comparator| comparator.matches(&version)))
+    }
+}
+
+/// Parses the numeric part of a version like "8.1.2", "8.1" or "8.1.2-1ubuntu2".
+/// Missing minor and patch numbers are considered as zero.
+pub(crate) fn parse_version_numbers(version: &str) -> Option<VersionNumbers> {
+    let numbers = parse_partial_version(version)?;
+
+    Some((numbers[0], *numbers.get(1).unwrap_or(&0), *numbers.get(2).unwrap_or(&0)))
+}
+
+/// Returns the numbers that are explicitly present in the version,
+/// so that wildcards and partial versions can be told apart from complete ones.
+fn parse_partial_version(version: &str) -> Option<Vec<u64>> {
+    let version = version.trim().trim_start_matches(['v', 'V']);
+    // Composer stability flags, like "@dev", are not relevant to PHP binaries.
+    let version = version.split('@').next().unwrap_or("");
+
+    let mut numbers = Vec::new();
+
+    for part in version.split('.') {
+        if part == "*" || part.eq_ignore_ascii_case("x") {
+            break;
+        }
+
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        if digits.is_empty() {
+            return None;
+        }
+
+        numbers.push(digits.parse().ok()?);
+
+        // A suffix like "-dev" or "RC1" ends the numeric part of the version.
+        if digits.len() != part.len() || numbers.len() == 3 {
+            break;
+        }
+    }
+
+    if numbers.is_empty() {
+        None
+    } else {
+        Some(numbers)
+    }
+}
+
+const OPERATORS: [&str; 10] = ["==", ">=", "<=", "!=", "<>", "=", ">", "<", "^", "~"];
+
+/// Parses a range like "8.1 - 8.3", where a partial upper version includes all its versions, like Composer does.
+fn parse_hyphen_range(lower: &str, upper: &str) -> Result<Vec<Comparator>, VersionConstraintError> {
+    let invalid = || VersionConstraintError(format!("Invalid version range \"{} - {}\"", lower, upper));
+
+    let lower = parse_partial_version(lower).ok_or_else(invalid)?;
+    let upper = parse_partial_version(upper).ok_or_else(invalid)?;
+
+    let upper_bound = match upper.as_slice() {
+        [major] => Comparator::new(Operator::LessThan, (major + 1, 0, 0)),
+        [major, minor] => Comparator::new(Operator::LessThan, (*major, minor + 1, 0)),
+        [major, minor, patch, ..] => Comparator::new(Operator::LessThanOrEqual, (*major, *minor, *patch)),
+        [] => return Err(invalid()),
+    };
+
+    Ok(vec![
+        Comparator::new(
+            Operator::GreaterThanOrEqual,
+            (lower[0], *lower.get(1).unwrap_or(&0), *lower.get(2).unwrap_or(&0)),
+        ),
+        upper_bound,
+    ])
+}
+
+fn parse_term(term: &str) -> Result<Vec<Comparator>, VersionConstraintError> {
+    let invalid = || VersionConstraintError(format!("Invalid version constraint \"{}\"", term));
+
+    if term == "*" {
+        return Ok(vec![Comparator::new(Operator::GreaterThanOrEqual, (0, 0, 0))]);
+    }
+
+    let operator = OPERATORS.iter().find(|operator| term.starts_with(*operator)).copied().unwrap_or("");
+
+    let numbers = parse_partial_version(&term[operator.len()..]).ok_or_else(invalid)?;
+    let version = (numbers[0], *numbers.get(1).unwrap_or(&0), *numbers.get(2).unwrap_or(&0));
+
+    let comparators = match operator {
+        ">=" => vec![Comparator::new(Operator::GreaterThanOrEqual, version)],
+        "<=" => vec![Comparator::new(Operator::LessThanOrEqual, version)],
+        ">" => vec![Comparator::new(Operator::GreaterThan, version)],
+        "<" => vec![Comparator::new(Operator::LessThan, version)],
+        "!=" | "<>" => {
+            if numbers.len() == 3 {
+                vec![Comparator::new(Operator::NotEqual, version)]
+            } else {
+                return Err(VersionConstraintError(format!(
+                    "Partial versions are not supported with \"!=\" in \"{}\"",
+                    term
+                )));
+            }
+        },
+        "^" => {
+            let upper_bound = match numbers.as_slice() {
+                [0, 0, patch] => (0, 0, patch + 1),
+                [0, minor, ..] => (0, minor + 1, 0),
+                [0] => (1, 0, 0),
+                [major, ..] => (major + 1, 0, 0),
+                [] => return Err(invalid()),
+            };
+            vec![
+                Comparator::new(Operator::GreaterThanOrEqual, version),
+                Comparator::new(Operator::LessThan, upper_bound),
+            ]
+        },
+        "~" => {
+            let upper_bound = match numbers.as_slice() {
+                [major] | [major, _] => (major + 1, 0, 0),
+                [major, minor, _] => (*major, minor + 1, 0),
+                _ => return Err(invalid()),
+            };
+            vec![
+                Comparator::new(Operator::GreaterThanOrEqual, version),
+                Comparator::new(Operator::LessThan, upper_bound),
+            ]
+        },
+        _ => {
+            // No operator, or "=": partial versions and wildcards match the whole range.
+            match numbers.as_slice() {
+                [major] => vec![
+                    Comparator::new(Operator::GreaterThanOrEqual, version),
+                    Comparator::new(Operator::LessThan, (major + 1, 0, 0)),
+                ],
+                [major, minor] => vec![
+                    Comparator::new(Operator::GreaterThanOrEqual, version),
+                    Comparator::new(Operator::LessThan, (*major, minor + 1, 0)),
+                ],
+                _ => vec![Comparator::new(Operator::Equal, version)],
+            }
+        },
+    };
+
+    Ok(comparators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(constraint: &str, version: &str) -> bool {
+        VersionConstraint::parse(constraint).unwrap().matches(version)
+    }
+
+    #[test]
+    fn partial_versions_match_whole_range() {
+        assert!(matches("8.2", "8.2.0"));
+        assert!(matches("8.2", "8.2.10"));
+        assert!(!matches("8.2", "8.20.1"));
+        assert!(!matches("8.1", "8.10.0"));
+        assert!(matches("8.*", "8.3.1"));
+        assert!(!matches("8.*", "7.4.33"));
+        assert!(matches("8.1.*", "8.1.27"));
+        assert!(matches("8.1.2", "8.1.2"));
+        assert!(!matches("8.1.2", "8.1.20"));
+    }
+
+    #[test]
+    fn caret_and_tilde() {
+        assert!(matches("^8.1", "8.1.0"));
+        assert!(matches("^8.1", "8.3.2"));
+        assert!(!matches("^8.1", "8.0.30"));
+        assert!(!matches("^8.1", "9.0.0"));
+        assert!(matches("~8.1.2", "8.1.9"));
+        assert!(!matches("~8.1.2", "8.2.0"));
+        assert!(matches("~8.1", "8.9.0"));
+    }
+
+    #[test]
+    fn ranges_and_alternatives() {
+        assert!(matches(">=8.1 <8.3", "8.2.10"));
+        assert!(!matches(">=8.1 <8.3", "8.3.0"));
+        assert!(matches(">=8.1,<8.3", "8.1.0"));
+        assert!(matches("^7.4 || ^8.0", "7.4.33"));
+        assert!(matches("^7.4 || ^8.0", "8.2.1"));
+        assert!(!matches("^7.4 || ^8.0", "7.3.0"));
+        assert!(matches(">=8.1 <8.3 || 8.4.*", "8.4.1"));
+    }
+
+    #[test]
+    fn operators_separated_from_versions() {
+        assert!(matches(">= 8.1", "8.1.0"));
+        assert!(!matches(">= 8.1", "8.0.30"));
+        assert!(matches(">= 8.1, < 8.3", "8.2.10"));
+        assert!(!matches(">= 8.1 < 8.3", "8.3.0"));
+        assert!(matches("^ 8.1 || ~ 7.4.0", "7.4.33"));
+    }
+
+    #[test]
+    fn hyphen_ranges() {
+        assert!(matches("8.1 - 8.3", "8.1.0"));
+        assert!(matches("8.1 - 8.3", "8.3.12"));
+        assert!(!matches("8.1 - 8.3", "8.4.0"));
+        assert!(!matches("8.1 - 8.3", "8.0.30"));
+        assert!(matches("8.1.2 - 8.2.5", "8.2.5"));
+        assert!(!matches("8.1.2 - 8.2.5", "8.2.6"));
+        assert!(!matches("8.1.2 - 8.2.5", "8.1.1"));
+        assert!(matches("7.4 - 8 || 9.0.*", "8.9.0"));
+        assert!(VersionConstraint::parse("8.1 -").is_err());
+    }
+
+    #[test]
+    fn versions_are_compared_semantically() {
+        assert!(matches(">8.9", "8.10.0"));
+        assert!(matches("^8.9", "8.10.0"));
+    }
+
+    #[test]
+    fn versions_with_suffixes() {
+        assert!(matches("8.1", "8.1.2-1ubuntu2.14"));
+        assert!(matches("^8.4", "8.4.0RC1"));
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        assert!(VersionConstraint::parse("").is_err());
+        assert!(VersionConstraint::parse("latest").is_err());
+        assert!(VersionConstraint::parse(">=8.1 ||").is_err());
+    }
+}
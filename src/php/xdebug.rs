@@ -0,0 +1,147 @@
+use crate::config::paths;
+use crate::php::structs::PhpBinary;
+use std::error::Error;
+use std::fmt;
+use std::fs::read_to_string;
+use std::fs::remove_file;
+use std::fs::write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+pub(crate) const DEFAULT_MODE: &str = "debug";
+
+// @see https://xdebug.org/docs/all_settings#mode
+const MODES: [&str; 7] = ["off", "develop", "coverage", "debug", "gcstats", "profile", "trace"];
+
+#[derive(Debug)]
+struct XdebugError(String);
+
+impl fmt::Display for XdebugError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "An error occured: {}", self.0)
+    }
+}
+
+impl Error for XdebugError {}
+
+/// Checks a value of the "xdebug.mode" setting, which can contain several modes separated by commas.
+pub(crate) fn validate_mode(mode: &str) -> Result<(), Box<dyn Error>> {
+    for part in mode.split(',') {
+        if !MODES.contains(&part.trim()) {
+            return Err(Box::new(XdebugError(format!(
+                "Invalid Xdebug mode \"{}\". Allowed modes: {}.",
+                part.trim(),
+                MODES.join(", ")
+            ))));
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the Xdebug mode of the project's PHP server, or None if Xdebug is disabled.
+pub(crate) fn get_mode() -> Option<String> {
+    read_to_string(paths::get_xdebug_mode_file()).ok().map(|mode| mode.trim().to_string())
+}
+
+pub(crate) fn set_mode(mode: Option<&str>) {
+    let mode_file = paths::get_xdebug_mode_file();
+
+    match mode {
+        Some(mode) => write(&mode_file, mode).expect("Could not write Xdebug mode to file."),
+        None => remove_file(&mode_file).unwrap_or_default(),
+    }
+}
+
+/// Returns the "-d" arguments enabling Xdebug in the PHP server, so that it is never loaded in other PHP processes.
+/// If Xdebug is already loaded by the PHP configuration, only its mode is changed.
+pub(crate) fn get_server_arguments(
+    binary: &PhpBinary,
+    server_bin: &str,
+    mode: &str,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut arguments = Vec::new();
+
+    if !is_loaded(server_bin) {
+        let extension = find_extension(binary)?;
+        arguments.push(String::from("-d"));
+        arguments.push(format!("zend_extension={}", extension.to_str().unwrap()));
+    }
+
+    arguments.push(String::from("-d"));
+    arguments.push(format!("xdebug.mode={}", mode));
+
+    Ok(arguments)
+}
+
+fn is_loaded(php_bin: &str) -> bool {
+    match Command::new(php_bin).arg("-m").output() {
+        Ok(output) => {
+            String::from_utf8_lossy(&output.stdout).lines().any(|line| line.trim().eq_ignore_ascii_case("xdebug"))
+        },
+        Err(e) => {
+            debug!("Could not list modules of {}: {}", php_bin, e);
+            false
+        },
+    }
+}
+
+fn find_extension(binary: &PhpBinary) -> Result<PathBuf, Box<dyn Error>> {
+    if binary.cli().is_empty() {
+        return Err(Box::new(XdebugError(String::from(
+            "The PHP CLI is needed to find the Xdebug extension, and it was not found for this PHP version.",
+        ))));
+    }
+
+    let output = Command::new(binary.cli()).arg("-r").arg("echo ini_get('extension_dir');").output()?;
+    let extension_dir = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if !output.status.success() || extension_dir.is_empty() {
+        return Err(Box::new(XdebugError(format!("Could not find the extensions directory of {}.", binary.cli()))));
+    }
+
+    find_extension_in_dir(Path::new(&extension_dir)).ok_or_else(|| {
+        Box::new(XdebugError(format!(
+            "Xdebug is not installed for {}: no Xdebug extension was found in \"{}\". You can install it with \"pecl install xdebug\" or with your package manager.",
+            binary.cli(),
+            extension_dir
+        ))) as Box<dyn Error>
+    })
+}
+
+fn find_extension_in_dir(extension_dir: &Path) -> Option<PathBuf> {
+    let extension = extension_dir.join("xdebug.so");
+    if extension.is_file() {
+        return Some(extension);
+    }
+
+    // Windows builds are named like "php_xdebug.dll" or "php_xdebug-3.2.0-8.2-vs16-x86_64.dll".
+    let pattern = PathBuf::from(glob::Pattern::escape(extension_dir.to_str()?)).join("php_xdebug*.dll");
+    glob::glob(pattern.to_str()?).ok()?.filter_map(Result::ok).find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modes_are_validated() {
+        assert!(validate_mode("debug").is_ok());
+        assert!(validate_mode("develop,debug").is_ok());
+        assert!(validate_mode("debug,step").is_err());
+    }
+
+    #[test]
+    fn windows_extension_is_found() {
+        let extension_dir = std::env::temp_dir().join("rymfony_xdebug_test");
+        std::fs::create_dir_all(&extension_dir).unwrap();
+        write(extension_dir.join("php_xdebug-3.2.0-8.2-vs16-x86_64.dll"), "").unwrap();
+
+        let extension = find_extension_in_dir(&extension_dir);
+
+        std::fs::remove_dir_all(&extension_dir).unwrap();
+
+        assert_eq!(extension, Some(extension_dir.join("php_xdebug-3.2.0-8.2-vs16-x86_64.dll")));
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+pub(crate) fn now() -> String {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|duration| duration.as_secs_f64()).unwrap_or(0.0);
+
+    format_timestamp(timestamp)
+}
+
+/// Formats a Unix timestamp (in seconds) to ISO 8601 format, in UTC.
+pub(crate) fn format_timestamp(timestamp: f64) -> String {
+    let millis_total = (timestamp * 1000.0).round() as i64;
+    let seconds_total = millis_total.div_euclid(1000);
+    let millis = millis_total.rem_euclid(1000);
+    let days = seconds_total.div_euclid(86400);
+    let seconds_of_day = seconds_total.rem_euclid(86400);
+
+    // Converts days since the Unix epoch to a civil date.
+    // @see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        (seconds_of_day % 3600) / 60,
+        seconds_of_day % 60,
+        millis
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_timestamp_as_iso_8601() {
+        assert_eq!(format_timestamp(1672531200.5), "2023-01-01T00:00:00.500Z");
+        assert_eq!(format_timestamp(951825600.0), "2000-02-29T12:00:00.000Z");
+    }
+}
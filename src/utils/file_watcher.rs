@@ -0,0 +1,111 @@
+use glob::glob;
+use glob::Pattern;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+
+// Editors and tools like Composer write several files in a row, which must trigger a single change.
+const DEBOUNCE_DELAY: Duration = Duration::from_millis(500);
+
+/// Detects changes of the files matching glob patterns, by comparing their modification dates when polled.
+pub(crate) struct FileWatcher {
+    base_dir: PathBuf,
+    patterns: Vec<String>,
+    files: HashMap<PathBuf, Option<SystemTime>>,
+    pending_change: Option<(Instant, PathBuf)>,
+}
+
+impl FileWatcher {
+    /// Patterns are relative to the base directory.
+    pub(crate) fn new(base_dir: &Path, patterns: Vec<String>) -> FileWatcher {
+        let mut watcher =
+            FileWatcher { base_dir: base_dir.to_path_buf(), patterns, files: HashMap::new(), pending_change: None };
+        watcher.files = watcher.scan();
+
+        watcher
+    }
+
+    pub(crate) fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    /// Returns the file that triggered a change, once no other change happened for a moment.
+    pub(crate) fn poll(&mut self) -> Option<PathBuf> {
+        let files = self.scan();
+
+        let changed_file = files
+            .iter()
+            .find(|(path, modified)| self.files.get(*path) != Some(modified))
+            .map(|(path, _)| path.clone())
+            .or_else(|| self.files.keys().find(|path| !files.contains_key(*path)).cloned());
+
+        self.files = files;
+
+        if let Some(changed_file) = changed_file {
+            self.pending_change = Some((Instant::now(), changed_file));
+
+            return None;
+        }
+
+        match &self.pending_change {
+            Some((changed_at, _)) if changed_at.elapsed() >= DEBOUNCE_DELAY => {
+                self.pending_change.take().map(|(_, changed_file)| changed_file)
+            },
+            _ => None,
+        }
+    }
+
+    fn scan(&self) -> HashMap<PathBuf, Option<SystemTime>> {
+        let mut files = HashMap::new();
+
+        for pattern in self.patterns.iter() {
+            // Only the watched pattern is a glob, the project path may contain characters like "[".
+            let pattern = PathBuf::from(Pattern::escape(self.base_dir.to_str().unwrap())).join(pattern);
+            let paths = match glob(pattern.to_str().unwrap()) {
+                Ok(paths) => paths,
+                Err(e) => {
+                    warn!("Invalid watched files pattern \"{}\": {}", pattern.to_str().unwrap(), e);
+                    continue;
+                },
+            };
+
+            for path in paths.flatten().filter(|path| path.is_file()) {
+                let modified = path.metadata().and_then(|metadata| metadata.modified()).ok();
+                files.insert(path, modified);
+            }
+        }
+
+        files
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::create_dir_all;
+    use std::fs::remove_dir_all;
+    use std::fs::write;
+    use std::thread;
+
+    #[test]
+    fn changes_are_debounced() {
+        let base_dir = std::env::temp_dir().join("rymfony_file_watcher_test [dev]");
+        create_dir_all(base_dir.join("config")).unwrap();
+        write(base_dir.join(".env"), "APP_ENV=dev").unwrap();
+
+        let mut watcher = FileWatcher::new(&base_dir, vec![String::from(".env*"), String::from("config/*.php")]);
+        assert_eq!(watcher.poll(), None);
+
+        write(base_dir.join("config").join("preload.php"), "<?php").unwrap();
+        assert_eq!(watcher.poll(), None);
+
+        thread::sleep(DEBOUNCE_DELAY);
+        assert_eq!(watcher.poll(), Some(base_dir.join("config").join("preload.php")));
+        assert_eq!(watcher.poll(), None);
+
+        remove_dir_all(&base_dir).unwrap();
+    }
+}
@@ -1,10 +1,12 @@
 use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::time::Duration;
 
 pub(crate) fn find_available_port(start_from_port: u16) -> u16 {
     for port in start_from_port..65535 {
-        match TcpListener::bind(("127.0.0.1", port)) {
-            Ok(_p) => return port,
-            _ => {},
+        if let Ok(_p) = TcpListener::bind(("127.0.0.1", port)) {
+            return port;
         }
     }
 
@@ -23,3 +25,12 @@ pub(crate) fn parse_default_port(port: &str, fallback_port: &str) -> u16 {
 
     fallback_port.parse::<u16>().unwrap()
 }
+
+pub(crate) fn is_address_open(host: &str, port: u16) -> bool {
+    let addresses = match (host, port).to_socket_addrs() {
+        Ok(addresses) => addresses,
+        Err(_) => return false,
+    };
+
+    addresses.into_iter().any(|address| TcpStream::connect_timeout(&address, Duration::from_millis(500)).is_ok())
+}
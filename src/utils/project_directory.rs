@@ -3,10 +3,18 @@ use std::error::Error;
 use std::fmt;
 use std::fs::create_dir_all;
 use std::fs::remove_file;
+use std::fs::rename;
+use std::path::Component;
+use std::path::Path;
 use std::path::PathBuf;
 use std::result::Result;
+use std::sync::Once;
+use std::sync::OnceLock;
 
 use crate::config::paths;
+use crate::config::project_config::PROJECT_CONFIG_FILE;
+use crate::config::projects;
+use crate::php::binaries::PHP_VERSION_FILE;
 use dirs::home_dir;
 use sha2::Digest;
 
@@ -21,45 +29,219 @@ impl fmt::Display for ProjectDirectoryError {
 
 impl Error for ProjectDirectoryError {}
 
+/// Files marking the root directory of a project.
+const PROJECT_ROOT_MARKERS: [&str; 3] = ["composer.json", PHP_VERSION_FILE, PROJECT_CONFIG_FILE];
+
+static PROJECT_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Sets the project root given with the "--project-dir" option, before it is used by any command.
+pub(crate) fn set_project_root(path: &Path) -> Result<(), Box<dyn Error>> {
+    let path = env::current_dir()?.join(path);
+
+    if !path.is_dir() {
+        return Err(Box::new(ProjectDirectoryError(format!("\"{}\" is not a directory.", path.display()))));
+    }
+
+    PROJECT_ROOT.set(path).unwrap_or_default();
+
+    Ok(())
+}
+
+/// Returns the root directory of the project, which is the nearest directory containing a "composer.json",
+/// ".php-version" or ".rymfony.toml" file, from the current directory up to the home directory.
+/// Defaults to the current directory when none is found.
+pub(crate) fn get_project_root() -> PathBuf {
+    PROJECT_ROOT
+        .get_or_init(|| {
+            let cwd = env::current_dir().unwrap();
+
+            find_project_root(&cwd, home_dir().as_deref()).unwrap_or(cwd)
+        })
+        .clone()
+}
+
+// The home directory is excluded, since it often contains files like a global ".php-version".
+fn find_project_root(start: &Path, home: Option<&Path>) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .take_while(|directory| Some(*directory) != home)
+        .find(|directory| PROJECT_ROOT_MARKERS.iter().any(|marker| directory.join(marker).is_file()))
+        .map(Path::to_path_buf)
+}
+
 pub(crate) fn clean_rymfony_runtime_files() {
     remove_file(paths::rymfony_pid_file()).unwrap_or_default();
+    remove_file(paths::get_serve_arguments_file()).unwrap_or_default();
     remove_file(paths::php_server_pid_file()).unwrap_or_default();
     remove_file(paths::get_caddy_pid_file()).unwrap_or_default();
     remove_file(paths::get_caddy_runtime_config_file()).unwrap_or_default();
+    remove_file(paths::get_xdebug_mode_file()).unwrap_or_default();
 }
 
+/// Removes the PHP-FPM and Caddy configs of the project, so that they are created again from Rymfony's templates.
+pub(crate) fn reset_rymfony_config_files() {
+    let mut config_files = vec![paths::get_caddy_config_file()];
+    #[cfg(not(target_family = "windows"))]
+    config_files.push(paths::php_fpm_conf_ini_file());
+
+    for config_file in config_files {
+        if config_file.exists() {
+            remove_file(&config_file).expect("Could not remove config file.");
+            info!("Removed \"{}\", it will be created again.", config_file.to_str().unwrap());
+        }
+    }
+}
+
+// The registry is updated once per command, since the project directory is used by most paths.
+static REGISTER_PROJECT: Once = Once::new();
+
 pub(crate) fn get_rymfony_project_directory() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let home = home_dir().unwrap().display().to_string();
     let homestr = home.as_str();
 
-    if homestr != "" {
-        let cwd = env::current_dir().unwrap();
+    if !homestr.is_empty() {
+        let project_root = get_project_root();
+        let project_path = canonical_project_path(&project_root);
+        let hash = project_hash(&project_path);
+
+        let rymfony_home = PathBuf::from(homestr).join(".rymfony");
+        let rymfony_project_path = rymfony_home.join(&hash);
 
-        let mut hasher = sha2::Sha256::new();
-        hasher.update(&cwd.to_str().unwrap().as_bytes());
-        let hash = hasher.finalize();
+        if !rymfony_project_path.is_dir() {
+            // Directories created before paths were canonicalized are named after the hash of the raw path.
+            let legacy_project_path = rymfony_home.join(hash_path(&project_root));
 
-        let rymfony_project_path = PathBuf::from(homestr).join(".rymfony").join(format!("{:x}", hash));
+            if legacy_project_path != rymfony_project_path && legacy_project_path.is_dir() {
+                // A server started by a previous version keeps its directory until it is stopped.
+                if legacy_project_path.join(".rymfony.pid").exists() {
+                    return Ok(legacy_project_path);
+                }
+
+                debug!("Moving Rymfony directory of the project to \"{}\".", rymfony_project_path.to_str().unwrap());
+                rename(&legacy_project_path, &rymfony_project_path).unwrap_or_default();
+            }
+        }
 
         if !rymfony_project_path.is_dir() {
-            create_dir_all(&rymfony_project_path).expect(
-                format!("Unable to create directory for project {}", rymfony_project_path.to_str().unwrap()).as_str(),
-            );
+            create_dir_all(&rymfony_project_path).unwrap_or_else(|_| {
+                panic!("Unable to create directory for project {}", rymfony_project_path.to_str().unwrap())
+            });
         }
 
         create_log_directory(&rymfony_project_path);
 
+        REGISTER_PROJECT.call_once(|| {
+            let mut registry = projects::load();
+            projects::register(&mut registry, &hash, &project_path);
+            if let Err(e) = projects::save(&registry) {
+                debug!("Could not update the projects registry: {}", e);
+            }
+        });
+
         return Ok(rymfony_project_path);
     }
 
     Err(Box::new(ProjectDirectoryError("Cannot find the \"HOME\" directory".into())))
 }
 
-fn create_log_directory(rymfony_project_path: &PathBuf) {
+/// Returns the path identifying a project, so that it gets the same Rymfony directory however it is opened:
+/// symlinks are resolved, and a path that does not exist anymore is only made absolute.
+pub(crate) fn canonical_project_path(path: &Path) -> PathBuf {
+    let path = env::current_dir().unwrap().join(path);
+
+    match path.canonicalize() {
+        // Canonicalize on Windows leaves the "\\?\" prefix on canonicalized paths, which causes issues.
+        Ok(canonical) => match canonical.to_str().and_then(|canonical| canonical.strip_prefix(r"\\?\")) {
+            Some(canonical) => PathBuf::from(canonical),
+            None => canonical,
+        },
+        Err(_) => path.components().fold(PathBuf::new(), |mut normalized, component| {
+            match component {
+                Component::CurDir => (),
+                Component::ParentDir => {
+                    normalized.pop();
+                },
+                component => normalized.push(component),
+            }
+
+            normalized
+        }),
+    }
+}
+
+/// Returns the name of the Rymfony directory of a project.
+pub(crate) fn project_hash(project_path: &Path) -> String {
+    let path = project_path.to_str().unwrap();
+
+    // macOS and Windows filesystems ignore the case by default.
+    if cfg!(any(target_os = "macos", target_family = "windows")) {
+        return hash_path(Path::new(&path.to_lowercase()));
+    }
+
+    hash_path(project_path)
+}
+
+fn hash_path(path: &Path) -> String {
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(path.to_str().unwrap().as_bytes());
+
+    format!("{:x}", hasher.finalize())
+}
+
+fn create_log_directory(rymfony_project_path: &Path) {
     let log_dir = rymfony_project_path.join("log");
 
     if !log_dir.is_dir() {
         create_dir_all(&log_dir)
-            .expect(format!("Unable to create logs directory for project {}", log_dir.to_str().unwrap()).as_str());
+            .unwrap_or_else(|_| panic!("Unable to create logs directory for project {}", log_dir.to_str().unwrap()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn project_paths_are_canonicalized() {
+        let project_dir = env::temp_dir().join("rymfony_project_directory_test");
+        create_dir_all(project_dir.join("src")).unwrap();
+
+        let project_path = canonical_project_path(&project_dir);
+
+        assert_eq!(canonical_project_path(&project_dir.join("src").join("..")), project_path);
+        assert_eq!(project_hash(&canonical_project_path(&project_dir.join("."))), project_hash(&project_path));
+
+        #[cfg(not(target_family = "windows"))]
+        {
+            let link = env::temp_dir().join("rymfony_project_directory_test_link");
+            remove_file(&link).unwrap_or_default();
+            std::os::unix::fs::symlink(&project_dir, &link).unwrap();
+
+            assert_eq!(canonical_project_path(&link), project_path);
+            remove_file(&link).unwrap();
+        }
+
+        let moved_dir = project_path.join("moved").join("..").join("gone");
+        assert_eq!(canonical_project_path(&moved_dir), project_path.join("gone"));
+
+        std::fs::remove_dir_all(&project_dir).unwrap();
+    }
+
+    #[test]
+    fn project_root_is_the_nearest_directory_with_a_marker() {
+        let home = env::temp_dir().join("rymfony_project_root_test");
+        let project_dir = home.join("projects").join("app");
+        let controller_dir = project_dir.join("src").join("Controller");
+        create_dir_all(&controller_dir).unwrap();
+        std::fs::write(home.join(PHP_VERSION_FILE), "8.2").unwrap();
+
+        assert_eq!(find_project_root(&controller_dir, Some(&home)), None);
+
+        std::fs::write(project_dir.join("composer.json"), "{}").unwrap();
+
+        assert_eq!(find_project_root(&controller_dir, Some(&home)), Some(project_dir.clone()));
+        assert_eq!(find_project_root(&project_dir, Some(&home)), Some(project_dir));
+
+        std::fs::remove_dir_all(&home).unwrap();
     }
 }
@@ -0,0 +1,46 @@
+use std::io::stdin;
+use std::io::stdout;
+use std::io::Write;
+
+/// Whether the user can answer questions, i.e. the standard input is a terminal.
+pub(crate) fn is_interactive() -> bool {
+    atty::is(atty::Stream::Stdin)
+}
+
+/// Asks a question and returns the trimmed answer, or nothing when the input is closed, like with Ctrl-D.
+pub(crate) fn ask(question: &str) -> Option<String> {
+    print!("{} ", question);
+    stdout().flush().unwrap_or_default();
+
+    let mut answer = String::new();
+    match stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => {
+            println!();
+            None
+        },
+        Ok(_) => Some(answer.trim().to_string()),
+    }
+}
+
+/// Asks a yes/no question. An empty answer, a closed input or a non-interactive session returns the default value.
+pub(crate) fn confirm(question: &str, default: bool) -> bool {
+    if !is_interactive() {
+        return default;
+    }
+
+    let choices = if default { "[Y/n]" } else { "[y/N]" };
+
+    loop {
+        let answer = match ask(&format!("{} {}", question, choices)) {
+            Some(answer) => answer.to_lowercase(),
+            None => return default,
+        };
+
+        match answer.as_str() {
+            "" => return default,
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => println!("Please answer \"y\" or \"n\"."),
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use prettytable::format;
+use prettytable::format::LinePosition;
+use prettytable::Table;
+
+/// Creates a table drawn with box characters, used by all commands displaying tables.
+/// The inner lines are drawn below the titles with `LinePosition::Title`, or between all rows with `LinePosition::Intern`.
+pub(crate) fn new_table(inner_lines: LinePosition) -> Table {
+    let mut table = Table::new();
+    let format = format::FormatBuilder::new()
+        .column_separator('│')
+        .borders('│')
+        .separators(&[LinePosition::Top], format::LineSeparator::new('─', '┬', '┌', '┐'))
+        .separators(&[LinePosition::Bottom], format::LineSeparator::new('─', '┴', '└', '┘'))
+        .separators(&[inner_lines], format::LineSeparator::new('─', '┼', '├', '┤'))
+        .padding(1, 1)
+        .build();
+
+    table.set_format(format);
+
+    table
+}